@@ -1,70 +1,406 @@
 use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::BinaryHeap;
+use std::fs;
 use std::fs::File;
-use std::io;
-use std::io::BufRead;
+use std::io::{BufRead, BufReader};
 use std::path::Path;
-use log::{debug, info};
+use std::str::FromStr;
+use log::{debug, info, warn};
+use crate::utils::assignment;
+use crate::utils::checked;
+use crate::utils::counter::Counter;
+use crate::utils::external_sort::ExternalSorter;
+use crate::utils::parse;
 
-pub fn part_1() {
-    let (column1, column2) = load_input();
-    let mut heap1 = BinaryHeap::new();
-    let mut heap2 = BinaryHeap::new();
+/*
+    How load_input treats lines that don't hold exactly two locations.
+    Strict rejects them. Lenient keeps a lone location in the column it's written under
+    (indented means the right one) and skips lines without any, which can leave the lists
+    with different lengths. Part 1 then reports the locations left without a partner
+    and keeps them out of the sum.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ListMode {
+    Strict,
+    Lenient,
+}
+
+impl FromStr for ListMode {
+    type Err = String;
 
-    for i in 0..column1.len() {
-        heap1.push(Reverse(column1[i]));
-        heap2.push(Reverse(column2[i]));
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "strict" => Ok(ListMode::Strict),
+            "lenient" => Ok(ListMode::Lenient),
+            _ => Err(format!("Unknown list mode '{}', expected strict or lenient", text)),
+        }
     }
+}
+
+pub fn part_1(mode: ListMode) {
+    let (column1, column2) = load_input(mode);
+    let mut heap1: BinaryHeap<Reverse<i32>> = column1.into_iter().map(Reverse).collect();
+    let mut heap2: BinaryHeap<Reverse<i32>> = column2.into_iter().map(Reverse).collect();
 
     let mut differences = Vec::new();
-    while !heap1.is_empty() {
+    while !heap1.is_empty() && !heap2.is_empty() {
         let val1 = heap1.pop().unwrap();
         let val2 = heap2.pop().unwrap();
         debug!("{} - {}", val1.0, val2.0); // Reverse() has the worst notation ever
-        differences.push((val1.0 - val2.0).abs());
+        differences.push(checked::abs(checked::sub(val1.0, val2.0)));
     }
-    let sum = differences.iter().sum::<i32>();
+    warn_unmatched("left", heap1.into_iter().map(|val| val.0).collect());
+    warn_unmatched("right", heap2.into_iter().map(|val| val.0).collect());
+    let sum: i32 = checked::sum(differences);
     info!("Part 1 - Sum: {}", sum);
 }
 
-pub fn part_2() {
-    let (column1, column2) = load_input();
-    let mut occurrence_map = HashMap::new();
-    column2.iter().for_each(|&val| {
-        occurrence_map
-            .entry(val)
-            .and_modify(|count| *count += 1)
-            .or_insert(1);
-    });
+fn warn_unmatched(side: &str, mut unmatched: Vec<i32>) {
+    if !unmatched.is_empty() {
+        unmatched.sort_unstable();
+        warn!("{} locations on the {} have no partner and are left out: {:?}", unmatched.len(), side, unmatched);
+    }
+}
+
+/*
+    What pairing a left location with a right one costs, as a function of their difference.
+    Part 1 is AbsoluteDifference. For a convex cost, pairing both lists in sorted order is
+    optimal, anything else needs a full assignment.
+ */
+#[derive(Debug, Copy, Clone)]
+pub enum PairingCost {
+    AbsoluteDifference,
+    SquaredDifference,
+    // The absolute difference but never more than the cap, so outliers stop dominating
+    Capped(i64),
+    // Has no command line form, it's for calling optimal_pairing from code
+    #[allow(dead_code)]
+    Custom { cost: fn(i64) -> i64, convex: bool },
+}
+
+impl PairingCost {
+    pub fn cost(&self, left: i32, right: i32) -> i64 {
+        let difference = left as i64 - right as i64;
+        match self {
+            PairingCost::AbsoluteDifference => difference.abs(),
+            PairingCost::SquaredDifference => checked::mul(difference, difference),
+            PairingCost::Capped(cap) => difference.abs().min(*cap),
+            PairingCost::Custom { cost, .. } => cost(difference),
+        }
+    }
+
+    pub fn is_convex(&self) -> bool {
+        match self {
+            PairingCost::AbsoluteDifference | PairingCost::SquaredDifference => true,
+            PairingCost::Capped(_) => false,
+            PairingCost::Custom { convex, .. } => *convex,
+        }
+    }
+}
+
+// "absolute", "squared" or "capped:<n>", as given to --pairing-cost
+impl FromStr for PairingCost {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.split_once(':') {
+            None if text == "absolute" => Ok(PairingCost::AbsoluteDifference),
+            None if text == "squared" => Ok(PairingCost::SquaredDifference),
+            Some(("capped", cap)) => cap.parse()
+                .map(PairingCost::Capped)
+                .map_err(|_| format!("'{}' is not a valid cap", cap)),
+            _ => Err(format!("Unknown pairing cost '{}', expected absolute, squared or capped:<n>", text)),
+        }
+    }
+}
+
+// Lists of different lengths (lenient mode) leave the extra locations of the longer one unmatched
+#[derive(Debug, Clone)]
+pub struct Pairing {
+    pub(crate) pairs: Vec<(i32, i32)>,
+    pub(crate) total: i64,
+    pub(crate) unmatched_left: Vec<i32>,
+    pub(crate) unmatched_right: Vec<i32>,
+}
+
+/*
+    Pairs every location of the shorter list with one of the longer list at the lowest total cost.
+    Sorted order is only guaranteed optimal for a convex cost on lists of the same length,
+    with different lengths it also matters which locations are left out, so that goes through
+    a full assignment like non-convex costs do.
+ */
+pub fn optimal_pairing(column1: &[i32], column2: &[i32], cost: PairingCost) -> Pairing {
+    if cost.is_convex() && column1.len() == column2.len() {
+        let mut sorted1 = column1.to_vec();
+        let mut sorted2 = column2.to_vec();
+        sorted1.sort_unstable();
+        sorted2.sort_unstable();
+        let pairs: Vec<(i32, i32)> = sorted1.into_iter().zip(sorted2).collect();
+        let total = checked::sum(pairs.iter().map(|&(left, right)| cost.cost(left, right)));
+        return Pairing { pairs, total, unmatched_left: Vec::new(), unmatched_right: Vec::new() };
+    }
+
+    // The assignment needs at least as many columns as rows, so the shorter list gets the rows
+    let transposed = column1.len() > column2.len();
+    let (rows, columns) = if transposed { (column2, column1) } else { (column1, column2) };
+    let pair_of = |row: usize, column: usize| {
+        if transposed { (columns[column], rows[row]) } else { (rows[row], columns[column]) }
+    };
+    let costs: Vec<Vec<i64>> = (0..rows.len())
+        .map(|row| (0..columns.len()).map(|column| {
+            let (left, right) = pair_of(row, column);
+            cost.cost(left, right)
+        }).collect())
+        .collect();
+    let (_, assignment) = assignment::hungarian(&costs);
+
+    let mut used = vec![false; columns.len()];
+    let pairs: Vec<(i32, i32)> = assignment.iter().enumerate().map(|(row, &column)| {
+        used[column] = true;
+        pair_of(row, column)
+    }).collect();
+    let unmatched: Vec<i32> = (0..columns.len()).filter(|&column| !used[column]).map(|column| columns[column]).collect();
+    let total = checked::sum(pairs.iter().map(|&(left, right)| cost.cost(left, right)));
+    let (unmatched_left, unmatched_right) = if transposed { (unmatched, Vec::new()) } else { (Vec::new(), unmatched) };
+    Pairing { pairs, total, unmatched_left, unmatched_right }
+}
+
+// Part 1 under another cost, logging which locations were paired up
+pub fn part_1_with_cost(cost: PairingCost, mode: ListMode) {
+    let (column1, column2) = load_input(mode);
+    let pairing = optimal_pairing(&column1, &column2, cost);
+    for (left, right) in &pairing.pairs {
+        info!("{} <-> {} costs {}", left, right, cost.cost(*left, *right));
+    }
+    warn_unmatched("left", pairing.unmatched_left);
+    warn_unmatched("right", pairing.unmatched_right);
+    info!("Part 1 - Total cost ({:?}): {}", cost, pairing.total);
+}
+
+pub fn part_2(mode: ListMode) {
+    let (column1, column2) = load_input(mode);
+    let occurrence_map: Counter<i32, i32> = column2.iter().copied().collect();
     let mut result: i32 = 0;
     column1.iter().for_each(|&val| {
-        let multiplier: i32 = match occurrence_map.get(&val) {
-            Some(count) => *count,
-            None => 0
-        };
-        result += val * multiplier;
+        result = checked::add(result, checked::mul(val, occurrence_map.get(&val)));
     });
     info!("Part 2: {}", result)
 }
 
-fn load_input() -> (Vec<i32>, Vec<i32>) {
+/*
+    Where the part 1 and part 2 totals come from, for auditing.
+    A pair's distance is booked on its left location, and every location's share of
+    the similarity is its value times how often it appears in both lists.
+ */
+pub fn breakdown(mode: ListMode) {
+    let (mut column1, mut column2) = load_input(mode);
+    column1.sort_unstable();
+    column2.sort_unstable();
+
+    let mut distance: Counter<i32, i64> = Counter::new();
+    for (&val1, &val2) in column1.iter().zip(&column2) {
+        distance.add_n(val1, (val1 as i64 - val2 as i64).abs());
+    }
+    let occurrence_map: Counter<i32, i64> = column2.iter().copied().collect();
+    let mut similarity: Counter<i32, i64> = Counter::new();
+    for &val in &column1 {
+        similarity.add_n(val, val as i64 * occurrence_map.get(&val));
+    }
+    log_contributions("Part 1 distance", &distance);
+    log_contributions("Part 2 similarity", &similarity);
+}
+
+// Largest contribution first, locations that add nothing are left out
+fn log_contributions(name: &str, contributions: &Counter<i32, i64>) {
+    let total = contributions.total();
+    let mut entries: Vec<(i32, i64)> = contributions.iter()
+        .filter(|(_, &amount)| amount != 0)
+        .map(|(&location, &amount)| (location, amount))
+        .collect();
+    entries.sort_by(|(location_a, amount_a), (location_b, amount_b)| {
+        amount_b.abs().cmp(&amount_a.abs()).then(location_a.cmp(location_b))
+    });
+    info!("{} by location, {} in total:", name, total);
+    for (location, amount) in entries {
+        let share = amount as f64 * 100.0 / total as f64;
+        info!("{:>12} {:>16} {:>7.2}%", location, amount, share);
+    }
+}
+
+/*
+    Both parts for location lists too big to hold in memory, in chunk_size values at a time.
+    Each column goes through an external merge sort, then the distance is a lockstep walk
+    over the two sorted streams and the similarity a walk over equal values in both.
+ */
+pub fn part_1_and_2_external(chunk_size: usize, mode: ListMode) {
+    let (mut column1, mut column2) = sort_input_externally(chunk_size, mode);
+    if column1.len() != column2.len() {
+        warn!("Location lists have different lengths: {} and {}", column1.len(), column2.len());
+    }
+    debug!("Sorted {} and {} locations into {} and {} runs", column1.len(), column2.len(), column1.run_count(), column2.run_count());
+
+    let mut sum: i64 = 0;
+    let mut left = sorted_stream(&mut column1).peekable();
+    let mut right = sorted_stream(&mut column2).peekable();
+    while left.peek().is_some() && right.peek().is_some() {
+        let (val1, val2) = (left.next().unwrap(), right.next().unwrap());
+        sum = checked::add(sum, checked::abs(checked::sub(val1, val2)));
+    }
+    // Whichever list is longer still has its largest locations left, at most one of these is non-empty
+    warn_unmatched_stream("left", left);
+    warn_unmatched_stream("right", right);
+    info!("Part 1 - Sum: {}", sum);
+
+    let similarity = merge_similarity(sorted_stream(&mut column1), sorted_stream(&mut column2));
+    info!("Part 2: {}", similarity);
+}
+
+// Each value on the left scores value * times it appears on the right, both streams ascending
+fn merge_similarity(left: impl Iterator<Item = i64>, right: impl Iterator<Item = i64>) -> i64 {
+    let mut left = left.peekable();
+    let mut right = right.peekable();
+    let mut result: i64 = 0;
+    while let Some(val) = left.next() {
+        let mut left_count: i64 = 1;
+        while left.next_if_eq(&val).is_some() {
+            left_count += 1;
+        }
+        while right.next_if(|&other| other < val).is_some() {}
+        let mut right_count: i64 = 0;
+        while right.next_if_eq(&val).is_some() {
+            right_count += 1;
+        }
+        result = checked::add(result, checked::mul(val, checked::mul(left_count, right_count)));
+    }
+    result
+}
+
+/*
+    The leftovers of a list too big for memory could be huge too,
+    so only the first few are listed and the rest are just counted.
+ */
+fn warn_unmatched_stream(side: &str, unmatched: impl Iterator<Item = i64>) {
+    const LISTED: usize = 20;
+    let mut listed: Vec<i64> = Vec::new();
+    let mut count = 0;
+    for val in unmatched {
+        if listed.len() < LISTED {
+            listed.push(val);
+        }
+        count += 1;
+    }
+    if count > LISTED {
+        warn!("{} locations on the {} have no partner and are left out, the first {}: {:?}", count, side, LISTED, listed);
+    } else if count > 0 {
+        warn!("{} locations on the {} have no partner and are left out: {:?}", count, side, listed);
+    }
+}
+
+fn sorted_stream(sorter: &mut ExternalSorter) -> impl Iterator<Item = i64> {
+    sorter.sorted().expect("Failed to merge sorted runs").map(|value| value.expect("Failed to read sorted run"))
+}
+
+// Reads the input a line at a time so only one chunk per column is ever held in memory
+fn sort_input_externally(chunk_size: usize, mode: ListMode) -> (ExternalSorter, ExternalSorter) {
+    let path = Path::new("resources/day1.txt");
+    let file = File::open(path).unwrap_or_else(|_| panic!("File not found: {:?}", path.to_str()));
+    let mut column1 = ExternalSorter::new(chunk_size).expect("Failed to create sort directory");
+    let mut column2 = ExternalSorter::new(chunk_size).expect("Failed to create sort directory");
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.expect("Failed to read file");
+        let (col1, col2) = parse_locations::<i64>(&line, index + 1, mode);
+        if let Some(col1) = col1 {
+            column1.push(col1).expect("Failed to write sorted run");
+        }
+        if let Some(col2) = col2 {
+            column2.push(col2).expect("Failed to write sorted run");
+        }
+    }
+    (column1, column2)
+}
+
+fn load_input(mode: ListMode) -> (Vec<i32>, Vec<i32>) {
     let path = Path::new("resources/day1.txt");
     if !path.exists() {
         panic!("File not found: {:?}", path.to_str());
     }
-    let file = File::open(&path).expect("Failed to open file");
-    let reader = io::BufReader::new(file);
+    let input = fs::read_to_string(path).expect("Failed to open file");
 
-    let mut column1 = Vec::new();
-    let mut column2 = Vec::new();
+    let (mut column1, mut column2) = (Vec::new(), Vec::new());
+    for (index, line) in input.lines().enumerate() {
+        let (col1, col2) = parse_locations::<i32>(line, index + 1, mode);
+        column1.extend(col1);
+        column2.extend(col2);
+    }
+    if column1.len() != column2.len() {
+        warn!("Location lists have different lengths: {} and {}", column1.len(), column2.len());
+    }
+    (column1, column2)
+}
 
-    for line in reader.lines() {
-        let line = line.expect("Failed to read line");
-        let mut parts = line.split_whitespace();
-        if let (Some(col1), Some(col2)) = (parts.next(), parts.next()) {
-            column1.push(col1.parse::<i32>().expect("Failed to parse column 1"));
-            column2.push(col2.parse::<i32>().expect("Failed to parse column 2"));
+// The locations on one line, in lenient mode either of them can be missing
+fn parse_locations<T: FromStr + Copy>(line: &str, line_number: usize, mode: ListMode) -> (Option<T>, Option<T>) {
+    if line.trim().is_empty() {
+        return (None, None);
+    }
+    if mode == ListMode::Strict {
+        let [col1, col2]: [T; 2] = parse::record(line)
+            .map_err(|error| error.at_line(line_number))
+            .unwrap_or_else(|error| panic!("Failed to parse location lists: {}", error));
+        return (Some(col1), Some(col2));
+    }
+    let values: Vec<T> = parse::integers(line)
+        .map_err(|error| error.at_line(line_number))
+        .unwrap_or_else(|error| panic!("Failed to parse location lists: {}", error));
+    match values.as_slice() {
+        [] => (None, None),
+        [col1, col2] => (Some(*col1), Some(*col2)),
+        [val] if line.starts_with(char::is_whitespace) => (None, Some(*val)),
+        [val] => (Some(*val), None),
+        _ => {
+            warn!("Skipping line {}, it holds {} locations", line_number, values.len());
+            (None, None)
         }
     }
-    (column1, column2)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairing_leaves_the_extra_locations_of_the_longer_list_out() {
+        let pairing = optimal_pairing(&[1, 5, 9], &[9, 5], PairingCost::SquaredDifference);
+        assert_eq!(pairing.total, 0);
+        assert_eq!(pairing.unmatched_left, vec![1]);
+        assert!(pairing.unmatched_right.is_empty());
+
+        let pairing = optimal_pairing(&[4], &[1, 3, 8], PairingCost::AbsoluteDifference);
+        assert_eq!(pairing.pairs, vec![(4, 3)]);
+        assert_eq!(pairing.total, 1);
+        assert!(pairing.unmatched_left.is_empty());
+        assert_eq!(pairing.unmatched_right, vec![1, 8]);
+    }
+
+    #[test]
+    fn lenient_lines_can_miss_either_location() {
+        assert_eq!(parse_locations::<i32>("3   4", 1, ListMode::Lenient), (Some(3), Some(4)));
+        assert_eq!(parse_locations::<i32>("3", 1, ListMode::Lenient), (Some(3), None));
+        assert_eq!(parse_locations::<i32>("    4", 1, ListMode::Lenient), (None, Some(4)));
+        assert_eq!(parse_locations::<i32>("1 2 3", 1, ListMode::Lenient), (None, None));
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse location lists")]
+    fn strict_lines_need_both_locations() {
+        parse_locations::<i32>("3", 1, ListMode::Strict);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-arithmetic")]
+    #[should_panic(expected = "Overflow")]
+    fn squared_difference_past_i64_overflows() {
+        PairingCost::SquaredDifference.cost(i32::MIN, i32::MAX);
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::HashSet;
 use std::fs;
 use log::{debug, info};
+use crate::utils::neighbourhood::Neighbourhood;
+use crate::utils::parse;
 
 pub fn part_1() {
     let grid = load_input();
@@ -41,10 +43,9 @@ fn dfs(grid: &Vec<Vec<char>>, x: i32, y: i32, prev_val: i32, visited_trailheads:
        }
     }
     let mut count = 0;
-    count += dfs(grid, x + 1, y, grid_value, visited_trailheads);
-    count += dfs(grid, x - 1, y, grid_value, visited_trailheads);
-    count += dfs(grid, x, y + 1, grid_value, visited_trailheads);
-    count += dfs(grid, x, y - 1, grid_value, visited_trailheads);
+    for (next_x, next_y) in Neighbourhood::VonNeumann.neighbours(x, y) {
+        count += dfs(grid, next_x, next_y, grid_value, visited_trailheads);
+    }
     count
 }
 
@@ -81,10 +82,9 @@ fn dfs_part2(grid: &Vec<Vec<char>>, x: i32, y: i32, prev_val: i32) -> i32 {
         return 1;
     }
     let mut count = 0;
-    count += dfs_part2(grid, x + 1, y, grid_value);
-    count += dfs_part2(grid, x - 1, y, grid_value);
-    count += dfs_part2(grid, x, y + 1, grid_value);
-    count += dfs_part2(grid, x, y - 1, grid_value);
+    for (next_x, next_y) in Neighbourhood::VonNeumann.neighbours(x, y) {
+        count += dfs_part2(grid, next_x, next_y, grid_value);
+    }
     count
 }
 
@@ -95,13 +95,5 @@ fn is_out_of_bounds(grid: &Vec<Vec<char>>, x: i32, y: i32) -> bool {
 
 fn load_input() -> Vec<Vec<char>> {
     let input_string = fs::read_to_string("./resources/day10.txt").expect("failed to load input");
-    let mut grid: Vec<Vec<char>> = Vec::new();
-    input_string.lines().for_each(|line| {
-        let mut line_vec: Vec<char> = Vec::new();
-        line.chars().for_each(|c| {
-            line_vec.push(c);
-        });
-        grid.push(line_vec)
-    });
-    grid
+    parse::char_grid(&input_string).expect("failed to parse grid")
 }
\ No newline at end of file
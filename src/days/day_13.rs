@@ -1,11 +1,13 @@
-use std::collections::{HashMap, VecDeque};
 use std::{fs, thread};
 use std::ops::{Add, Sub};
 use std::thread::JoinHandle;
 use log::{debug, info};
 use num_bigint::BigInt;
-use regex::Regex;
-use num_traits::{Zero};
+use num_traits::{Signed, Zero};
+use crate::utils::checked;
+use crate::utils::memo::Memo;
+use crate::utils::numtheory;
+use crate::utils::parse;
 
 const COST_OF_A: i32 = 3;
 const COST_OF_B: i32 = 1;
@@ -70,19 +72,21 @@ pub fn part_1() {
     // Added threading for fun
     for definition in definitions {
         handles.push(thread::spawn(move || {
-            let mut memoizer: HashMap<Coordinate, Option<i32>> = HashMap::new(); // Memoize <position, minimum cost>
-            dfs_with_memoization(&definition.prize, &definition, &mut memoizer, &mut Coordinate::new(0, 0))
-                .unwrap_or_else(|| 0)
+            let mut memoizer: Memo<Coordinate, Option<i32>> = Memo::new(); // Memoize <position, minimum cost>
+            let cost = dfs_with_memoization(&definition.prize, &definition, &mut memoizer, &Coordinate::new(0, 0))
+                .unwrap_or(0);
+            debug!("Cache stats: {:?}", memoizer.stats());
+            cost
         }));
     };
 
     for handle in handles {
-        total_cost += handle.join().unwrap();
+        total_cost = checked::add(total_cost, handle.join().unwrap());
     }
     info!("Part 1: {}", total_cost);
 }
 
-fn dfs_with_memoization(remaining: &Coordinate, machine_definition: &MachineDefinition, memoizer: &mut HashMap<Coordinate, Option<i32>>, presses: &Coordinate) -> Option<i32> {
+fn dfs_with_memoization(remaining: &Coordinate, machine_definition: &MachineDefinition, memoizer: &mut Memo<Coordinate, Option<i32>>, presses: &Coordinate) -> Option<i32> {
     debug!("Remaining: {:?}, presses: {:?}", remaining, presses);
     if remaining.is_zero() {
         debug!("Found a solution: {:?}", presses);
@@ -96,28 +100,21 @@ fn dfs_with_memoization(remaining: &Coordinate, machine_definition: &MachineDefi
         debug!("Invalid position");
         return None
     }
-    if memoizer.contains_key(remaining) {
-        debug!("Found in memoizer: {:?}", remaining);
-        return *memoizer.get(remaining).unwrap();
-    }
 
-    let a_cost: Option<i32> = match dfs_with_memoization(&(remaining - &machine_definition.button_a), machine_definition, memoizer, &(presses + &Coordinate::new(1, 0))) {
-        Some(cost) => Some(cost + COST_OF_A),
-        None => None
-    };
-    let b_cost: Option<i32> = match dfs_with_memoization(&(remaining - &machine_definition.button_b), machine_definition, memoizer, &(presses + &Coordinate::new(0, 1))) {
-        Some(cost) => Some(cost + COST_OF_B),
-        None => None
-    };
-    let lowest_cost = match (a_cost, b_cost) {
-        (Some(a_cost), Some(b_cost)) => Some(a_cost.min(b_cost)),
-        (Some(a_cost), None) => Some(a_cost),
-        (None, Some(b_cost)) => Some(b_cost),
-        _ => None
-    };
-    debug!("Memoizing: {:?}: {:?}", remaining, lowest_cost);
-    memoizer.insert(remaining.clone(), lowest_cost);
-    lowest_cost
+    memoizer.get_or_compute(remaining.clone(), |memoizer| {
+        let a_cost: Option<i32> = dfs_with_memoization(&(remaining - &machine_definition.button_a), machine_definition, memoizer, &(presses + &Coordinate::new(1, 0)))
+            .map(|cost| cost + COST_OF_A);
+        let b_cost: Option<i32> = dfs_with_memoization(&(remaining - &machine_definition.button_b), machine_definition, memoizer, &(presses + &Coordinate::new(0, 1)))
+            .map(|cost| cost + COST_OF_B);
+        let lowest_cost = match (a_cost, b_cost) {
+            (Some(a_cost), Some(b_cost)) => Some(a_cost.min(b_cost)),
+            (Some(a_cost), None) => Some(a_cost),
+            (None, Some(b_cost)) => Some(b_cost),
+            _ => None
+        };
+        debug!("Memoizing: {:?}: {:?}", remaining, lowest_cost);
+        lowest_cost
+    })
 }
 
 /*
@@ -168,6 +165,16 @@ pub fn part_2() {
     for definition in definitions {
         let px: BigInt = BigInt::parse_bytes(offset, 10).unwrap() + definition.prize.x;
         let py: BigInt = BigInt::parse_bytes(offset, 10).unwrap()  + definition.prize.y;
+        let determinant = checked::sub(
+            checked::mul(definition.button_a.x, definition.button_b.y),
+            checked::mul(definition.button_a.y, definition.button_b.x)
+        );
+        if determinant == 0 {
+            if let Some(cost) = cheapest_collinear_presses(&definition, &px, &py) {
+                total_cost += cost;
+            }
+            continue
+        }
         let b = apply_formula(
             definition.button_a.x,
             definition.button_a.y,
@@ -184,54 +191,85 @@ pub fn part_2() {
             &px,
             &py
         );
-        if a == None || b == None {
-            continue
+        match (a, b) {
+            (Some(a), Some(b)) if !a.is_negative() && !b.is_negative() => {
+                total_cost += (a * COST_OF_A) + (b * COST_OF_B);
+            }
+            _ => continue
         }
-        total_cost += (a.unwrap() * COST_OF_A) + (b.unwrap() * COST_OF_B);
     }
     info!("Part 2: {}", total_cost);
 }
 
 fn apply_formula(x1: i32, y1: i32, x2: i32, y2: i32, px: &BigInt, py: &BigInt) -> Option<BigInt> {
-    if !((x1*py - y1*px) % (x1*y2 - y1*x2)).is_zero() {
+    // The BigInt side can't overflow, but the denominator is plain i32 arithmetic
+    let denominator = checked::sub(checked::mul(x1, y2), checked::mul(y1, x2));
+    if !((x1*py - y1*px) % denominator).is_zero() {
      return None
     }
-    Some((x1*py - y1*px) / (x1*y2 - y1*x2))
+    Some((x1*py - y1*px) / denominator)
+}
+
+/*
+    When both buttons move along the same line the formula above divides by zero.
+    If the prize is on that line too, then any presses that reach it on one axis reach it on
+    the other as well, so this is the single equation ax*A + bx*B = px with A, B >= 0.
+    The cost is linear in the solutions' parameter so the cheapest one sits at an end of the range.
+ */
+fn cheapest_collinear_presses(definition: &MachineDefinition, px: &BigInt, py: &BigInt) -> Option<BigInt> {
+    let (ax, ay) = (BigInt::from(definition.button_a.x), BigInt::from(definition.button_a.y));
+    let (bx, by) = (BigInt::from(definition.button_b.x), BigInt::from(definition.button_b.y));
+    if &ax * py != &ay * px || &bx * py != &by * px {
+        return None
+    }
+    let solutions = if ax.is_zero() && bx.is_zero() {
+        numtheory::solve_diophantine(&ay, &by, py)?
+    } else {
+        numtheory::solve_diophantine(&ax, &bx, px)?
+    };
+    let (lower, upper) = solutions.non_negative_range()?;
+    [lower, upper].iter().flatten().map(|k| {
+        let (a, b) = solutions.at(k);
+        debug!("Collinear machine option: A={}, B={}", a, b);
+        a * COST_OF_A + b * COST_OF_B
+    }).min()
 }
 
 fn load_input() -> Vec<MachineDefinition> {
     let input_string = fs::read_to_string("./resources/day13.txt").expect("failed to load file");
-    let mut definitions: Vec<MachineDefinition> = Vec::new();
-    let mut lines: VecDeque<String> = input_string.lines().map(String::from).collect();
-
-    let re = Regex::new(r"(\d+),.*\D(\d+)").unwrap();
-    while !lines.is_empty() {
-        let button_a_str = lines.pop_front().unwrap();
-        let button_b_str = lines.pop_front().unwrap();
-        let prize_str = lines.pop_front().unwrap();
-        lines.pop_front(); // skip empty line
-        debug!("Button A: {}, Button B: {}, Prize: {}", button_a_str, button_b_str, prize_str);
-
-        let button_a_regex = re.captures(&*button_a_str).expect("Failed to parse A");
-        let button_b_regex = re.captures(&*button_b_str).expect("Failed to parse B");
-        let prize_regex = re.captures(&*prize_str).expect("Failed to parse B");
-
-        let button_a = Coordinate::new(
-            button_a_regex.get(1).expect("Failed to read X from A").as_str().parse().unwrap(),
-            button_a_regex.get(2).expect("Failed to read Y from A").as_str().parse().unwrap()
-        );
-        let button_b = Coordinate::new(
-            button_b_regex.get(1).expect("Failed to read X from B").as_str().parse().unwrap(),
-            button_b_regex.get(2).expect("Failed to read Y from B").as_str().parse().unwrap()
-        );
-        let prize = Coordinate::new(
-            prize_regex.get(1).expect("Failed to read X from Prize").as_str().parse().unwrap(),
-            prize_regex.get(2).expect("Failed to read Y from Prize").as_str().parse().unwrap()
-        );
-        definitions.push(
-            MachineDefinition::new(button_a, button_b, prize)
-        );
+    parse::sections(&input_string).iter().map(|section| {
+        debug!("Machine: {}", section);
+        let [ax, ay, bx, by, px, py] = parse::record(section).expect("Failed to parse machine");
+        MachineDefinition::new(Coordinate::new(ax, ay), Coordinate::new(bx, by), Coordinate::new(px, py))
+    }).collect()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    fn cheapest(a: (i32, i32), b: (i32, i32), prize: (i32, i32)) -> Option<BigInt> {
+        let definition = MachineDefinition::new(Coordinate::new(a.0, a.1), Coordinate::new(b.0, b.1), Coordinate::new(prize.0, prize.1));
+        cheapest_collinear_presses(&definition, &BigInt::from(prize.0), &BigInt::from(prize.1))
     }
-    definitions
-}
\ No newline at end of file
+
+    #[test]
+    fn collinear_machine_picks_the_cheapest_presses() {
+        // 4A + B = 9: (0, 9) costs 9, (1, 5) costs 8, (2, 1) costs 7
+        assert_eq!(cheapest((4, 4), (1, 1), (9, 9)), Some(BigInt::from(7)));
+        // Both buttons only move along y: 2A + B = 5 is cheapest with B alone
+        assert_eq!(cheapest((0, 2), (0, 1), (0, 5)), Some(BigInt::from(5)));
+    }
+
+    #[test]
+    fn collinear_machine_without_a_non_negative_solution() {
+        // 3A + 5B = 7 only has solutions with a negative press count, like A = 4, B = -1
+        assert_eq!(cheapest((3, 3), (5, 5), (7, 7)), None);
+        // 2A + 4B is always even
+        assert_eq!(cheapest((2, 2), (4, 4), (7, 7)), None);
+    }
+
+    #[test]
+    fn prize_off_the_line_is_unreachable() {
+        assert_eq!(cheapest((1, 1), (2, 2), (3, 4)), None);
+    }
+}
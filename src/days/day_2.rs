@@ -1,125 +1,350 @@
+use std::fmt;
+use std::fs;
 use std::fs::File;
-use std::io;
-use std::io::BufRead;
+use std::io::{BufWriter, Write};
 use std::path::Path;
+use std::str::FromStr;
 use log::{debug, info};
-use crate::days::day_2::SafetyTriggerResult::{Continue, EndOfLine, Terminate};
+use crate::utils::csv;
+use crate::utils::parse;
 
-const ALLOWABLE_DIFFERENCE: i32 = 3;
+// Which way the levels of a safe report may move
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+    Either,
+    // Either way, and levels may also stay the same between steps
+    NonStrict,
+}
 
-enum SafetyTriggerResult {
-    Terminate,
-    Continue,
-    EndOfLine,
+impl Trend {
+    fn directions(&self) -> &'static [bool] {
+        match self {
+            Trend::Increasing => &[true],
+            Trend::Decreasing => &[false],
+            Trend::Either | Trend::NonStrict => &[true, false],
+        }
+    }
 }
 
-pub fn part_1() {
-    let list_of_reports = load_input();
-    let valid_report_count = list_of_reports
-        .iter()
-        .filter(|report| {
-            check_if_valid(report)
-        })
-        .count();
-    info!("Part 1: {}", valid_report_count);
+impl FromStr for Trend {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "increasing" => Ok(Trend::Increasing),
+            "decreasing" => Ok(Trend::Decreasing),
+            "either" => Ok(Trend::Either),
+            "non-strict" => Ok(Trend::NonStrict),
+            _ => Err(format!("Unknown trend '{}', expected increasing, decreasing, either or non-strict", text)),
+        }
+    }
 }
 
-pub fn part_2() {
-    let list_of_reports = load_input();
-    let valid_report_count = list_of_reports
-        .iter()
-        .filter(|report| {
-            let forward_result = check_if_valid_with_safety(&report.iter().rev().cloned().collect());
-            let reverse_result = check_if_valid_with_safety(report);
+impl fmt::Display for Trend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Trend::Increasing => "increasing",
+            Trend::Decreasing => "decreasing",
+            Trend::Either => "either",
+            Trend::NonStrict => "non-strict",
+        };
+        write!(f, "{}", name)
+    }
+}
 
-            return forward_result || reverse_result;
-        })
-        .count();
-    info!("Part 2 {}", valid_report_count);
+/*
+    The rules a report has to follow to count as safe.
+    Written as "name: min=1 max=3 trend=either dampener=1", both on the command line and in
+    policy files. Settings that are left out keep the part 1 rules, and commas can be used
+    instead of spaces.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyPolicy {
+    pub(crate) name: String,
+    pub(crate) min_step: i32,
+    pub(crate) max_step: i32,
+    pub(crate) trend: Trend,
+    // How many bad levels the Problem Dampener may remove from one report
+    pub(crate) dampener_budget: usize,
 }
 
-fn check_if_valid(report: &Vec<i32>) -> bool {
-    let mut head = 0;
-    let mut tail = 1;
+// Far more than any report has levels, anything above it is a typo
+const MAX_DAMPENER_BUDGET: usize = 100;
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        Self { name: "part 1".to_string(), min_step: 1, max_step: 3, trend: Trend::Either, dampener_budget: 0 }
+    }
+}
+
+impl SafetyPolicy {
+    pub fn part_2() -> Self {
+        Self { name: "part 2".to_string(), dampener_budget: 1, ..Self::default() }
+    }
+
+    fn is_safe_step(&self, from: i32, to: i32, is_ascending: bool) -> bool {
+        let delta = if is_ascending { to - from } else { from - to };
+        (self.trend == Trend::NonStrict && delta == 0) || (self.min_step..=self.max_step).contains(&delta)
+    }
+}
 
-    // Unwrap because problem states that there will always be at least 2 numbers
-    let mut head_value = report.get(head).unwrap();
-    let mut tail_value = report.get(tail).unwrap();
+impl FromStr for SafetyPolicy {
+    type Err = String;
 
-    let is_ascending = head_value < tail_value;
-    loop {
-        if is_ascending != (head_value < tail_value) {
-            return false;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (name, settings) = text.split_once(':')
+            .ok_or_else(|| format!("Policy '{}' needs a name, e.g. 'wide: max=5'", text))?;
+        let mut policy = SafetyPolicy { name: name.trim().to_string(), ..SafetyPolicy::default() };
+        for setting in settings.split(|c: char| c == ',' || c.is_whitespace()).filter(|setting| !setting.is_empty()) {
+            let (key, value) = setting.split_once('=')
+                .ok_or_else(|| format!("Expected key=value but found '{}'", setting))?;
+            let invalid = || format!("'{}' is not a valid value for {}", value, key);
+            match key {
+                "min" => policy.min_step = value.parse().map_err(|_| invalid())?,
+                "max" => policy.max_step = value.parse().map_err(|_| invalid())?,
+                "trend" => policy.trend = value.parse()?,
+                "dampener" => policy.dampener_budget = value.parse().map_err(|_| invalid())?,
+                _ => return Err(format!("Unknown policy setting '{}'", key)),
+            }
         }
-        let delta = (head_value - tail_value).abs();
-        if delta > ALLOWABLE_DIFFERENCE || delta == 0 {
-            return false;
+        if policy.min_step < 0 || policy.min_step > policy.max_step {
+            return Err(format!("Policy '{}' needs 0 <= min <= max", policy.name));
         }
-        head += 1;
-        tail += 1;
-        head_value = report.get(head).unwrap();
-        tail_value = match report.get(tail) {
-            Some(value) => value,
-            None => { break; }
+        if policy.dampener_budget > MAX_DAMPENER_BUDGET {
+            return Err(format!("Policy '{}' can remove at most {} levels", policy.name, MAX_DAMPENER_BUDGET));
+        }
+        Ok(policy)
+    }
+}
+
+impl fmt::Display for SafetyPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: min={} max={} trend={} dampener={}",
+               self.name, self.min_step, self.max_step, self.trend, self.dampener_budget)
+    }
+}
+
+// The rule a step between two levels breaks
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rule {
+    TooLarge,
+    TooSmall,
+    Zero,
+    DirectionFlip,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Rule::TooLarge => "too large",
+            Rule::TooSmall => "too small",
+            Rule::Zero => "zero",
+            Rule::DirectionFlip => "direction flip",
         };
+        write!(f, "{}", name)
     }
-    true
 }
 
-fn check_if_valid_with_safety(original_report: &Vec<i32>) -> bool {
-    debug!("Original Report: {:?}", original_report);
-    let mut report = original_report.clone();
-    let mut head = 0;
-    let mut tail = 1;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub(crate) levels: (usize, usize),
+    pub(crate) delta: i32,
+    pub(crate) rule: Rule,
+}
 
-    let mut safety_triggered = false;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Safe,
+    // Safe once the dampener removed the levels at these indices
+    Dampened(Vec<usize>),
+    Unsafe,
+}
 
-    let mut head_value = report[head];
-    let mut tail_value = report[tail];
+/*
+    Why a report is or isn't safe under a policy.
+    The violation is the first step that breaks a rule in the report as written,
+    so dampened reports have one too and only reports that were safe all along don't.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub(crate) verdict: Verdict,
+    pub(crate) violation: Option<Violation>,
+}
 
-    let is_ascending = head_value < tail_value;
-    loop {
-        debug!("Head: {} - Tail: {}", head_value, tail_value);
-        if is_ascending != (head_value < tail_value) {
-            match handle_safety_trigger(&mut safety_triggered, &mut report, &tail, &mut tail_value) {
-                Terminate => return false,
-                EndOfLine => return true,
-                Continue => continue,
-            }
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.verdict {
+            Verdict::Safe => write!(f, "safe")?,
+            Verdict::Dampened(removed) => write!(f, "safe after removing levels {:?}", removed)?,
+            Verdict::Unsafe => write!(f, "unsafe")?,
         }
-        let delta = (head_value - tail_value).abs();
-        if delta > ALLOWABLE_DIFFERENCE || delta == 0 {
-            match handle_safety_trigger(&mut safety_triggered, &mut report, &tail, &mut tail_value) {
-                Terminate => return false,
-                EndOfLine => return true,
-                Continue => continue,
-            }
+        if let Some(violation) = &self.violation {
+            let (first, second) = violation.levels;
+            write!(f, ", levels {} and {} differ by {} ({})", first, second, violation.delta, violation.rule)?;
         }
-        head += 1;
-        tail += 1;
-        head_value = *report.get(head).unwrap();
-        tail_value = match report.get(tail) {
-            Some(value) => *value,
-            None => { break; }
-        };
+        Ok(())
     }
-    true
 }
 
-fn handle_safety_trigger(safety_triggered: &mut bool, report: &mut Vec<i32>, tail: &usize, tail_value: &mut i32) -> SafetyTriggerResult {
-    if *safety_triggered {
-        debug!("Safety Triggered - termination");
-        Terminate
-    } else {
-        debug!("Safety Triggered");
-        *safety_triggered = true;
-        report.remove(*tail);
-        *tail_value = match report.get(*tail) {
-            Some(value) => *value,
-            None => return EndOfLine
+pub fn diagnose(report: &[i32], policy: &SafetyPolicy) -> Diagnosis {
+    let violation = first_violation(report, policy);
+    let verdict = match (&violation, dampened_removals(report, policy)) {
+        (None, _) => Verdict::Safe,
+        (Some(_), Some(removed)) => Verdict::Dampened(removed),
+        (Some(_), None) => Verdict::Unsafe,
+    };
+    Diagnosis { verdict, violation }
+}
+
+// Without a fixed trend the first step that isn't flat decides which way the report goes
+fn first_violation(report: &[i32], policy: &SafetyPolicy) -> Option<Violation> {
+    let is_ascending = match policy.trend {
+        Trend::Increasing => true,
+        Trend::Decreasing => false,
+        Trend::Either | Trend::NonStrict => report.windows(2).find(|pair| pair[0] != pair[1]).is_none_or(|pair| pair[0] < pair[1]),
+    };
+    report.windows(2).enumerate().find_map(|(index, pair)| {
+        let delta = pair[1] - pair[0];
+        let rule = if delta == 0 {
+            if policy.trend == Trend::NonStrict || policy.min_step == 0 {
+                return None;
+            }
+            Rule::Zero
+        } else if (delta > 0) != is_ascending {
+            Rule::DirectionFlip
+        } else if delta.abs() > policy.max_step {
+            Rule::TooLarge
+        } else if delta.abs() < policy.min_step {
+            Rule::TooSmall
+        } else {
+            return None;
         };
-        Continue //continue
+        Some(Violation { levels: (index, index + 1), delta, rule })
+    })
+}
+
+// One policy per line, blank lines and lines starting with # are skipped
+pub fn load_policies(path: &Path) -> Result<Vec<SafetyPolicy>, String> {
+    let input = fs::read_to_string(path).map_err(|error| format!("Failed to read {:?}: {}", path, error))?;
+    input.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| line.parse().map_err(|error| format!("{:?} line {}: {}", path, index + 1, error)))
+        .collect()
+}
+
+pub fn part_1() {
+    let list_of_reports = load_input();
+    info!("Part 1: {}", count_safe_reports(&list_of_reports, &SafetyPolicy::default()));
+}
+
+pub fn part_2() {
+    let list_of_reports = load_input();
+    info!("Part 2 {}", count_safe_reports(&list_of_reports, &SafetyPolicy::part_2()));
+}
+
+// The same reports checked against every policy, for comparing rule sets side by side
+pub fn evaluate_policies(policies: &[SafetyPolicy]) {
+    let list_of_reports = load_input();
+    for policy in policies {
+        info!("{} -> {} safe of {}", policy, count_safe_reports(&list_of_reports, policy), list_of_reports.len());
+    }
+}
+
+fn count_safe_reports(list_of_reports: &[Vec<i32>], policy: &SafetyPolicy) -> usize {
+    list_of_reports
+        .iter()
+        .filter(|report| {
+            let diagnosis = diagnose(report, policy);
+            debug!("{:?}: {}", report, diagnosis);
+            diagnosis.verdict != Verdict::Unsafe
+        })
+        .count()
+}
+
+/*
+    Writes the diagnosis of every report under every policy as CSV, one row each:
+    policy,line,levels,verdict,removed,first_index,second_index,delta,rule
+    Lists of levels and indices are space separated, columns that don't apply are empty.
+ */
+pub fn export_diagnostics(path: &Path, policies: &[SafetyPolicy]) {
+    let list_of_reports = load_input();
+    let mut writer = BufWriter::new(File::create(path).expect("Failed to create diagnostics file"));
+    writeln!(writer, "policy,line,levels,verdict,removed,first_index,second_index,delta,rule")
+        .expect("Failed to write diagnostics");
+    for policy in policies {
+        for (index, report) in list_of_reports.iter().enumerate() {
+            let diagnosis = diagnose(report, policy);
+            let (verdict, removed) = match &diagnosis.verdict {
+                Verdict::Safe => ("safe", String::new()),
+                Verdict::Dampened(removed) => ("dampened", join(removed)),
+                Verdict::Unsafe => ("unsafe", String::new()),
+            };
+            let violation = match &diagnosis.violation {
+                Some(violation) => format!("{},{},{},{}", violation.levels.0, violation.levels.1, violation.delta, violation.rule),
+                None => ",,,".to_string(),
+            };
+            writeln!(writer, "{},{},{},{},{},{}", csv::field(&policy.name), index + 1, join(report), verdict, removed, violation)
+                .expect("Failed to write diagnostics");
+        }
     }
+    writer.flush().expect("Failed to write diagnostics");
+    info!("Wrote {} diagnoses to {:?}", policies.len() * list_of_reports.len(), path);
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values.iter().map(T::to_string).collect::<Vec<String>>().join(" ")
+}
+
+/*
+    The Problem Dampener: finds the fewest levels to remove so the report becomes safe,
+    and gives their indices if the policy's dampener budget allows removing that many.
+
+    For each direction, removals[i] is the fewest removals that leave a safe run of levels
+    ending with level i kept. Level i either starts the run (everything before it removed),
+    or follows some kept level j with every level between them removed. Looking further back
+    than budget + 1 levels can only remove too many, so this is O(n * k) per direction.
+ */
+pub fn dampened_removals(report: &[i32], policy: &SafetyPolicy) -> Option<Vec<usize>> {
+    if report.len() <= 1 {
+        return Some(Vec::new());
+    }
+    let max_removals = policy.dampener_budget;
+    let mut best: Option<Vec<usize>> = None;
+    for &is_ascending in policy.trend.directions() {
+        let mut removals: Vec<usize> = Vec::with_capacity(report.len());
+        let mut previous_kept: Vec<Option<usize>> = Vec::with_capacity(report.len());
+        for i in 0..report.len() {
+            let mut fewest = (i, None);
+            for j in i.saturating_sub(max_removals.saturating_add(1))..i {
+                let candidate = removals[j] + (i - j - 1);
+                if candidate < fewest.0 && policy.is_safe_step(report[j], report[i], is_ascending) {
+                    fewest = (candidate, Some(j));
+                }
+            }
+            removals.push(fewest.0);
+            previous_kept.push(fewest.1);
+        }
+
+        let (last_kept, total) = (0..report.len())
+            .map(|i| (i, removals[i] + (report.len() - 1 - i)))
+            .min_by_key(|&(_, total)| total)
+            .unwrap();
+        if total > max_removals || best.as_ref().is_some_and(|best| best.len() <= total) {
+            continue;
+        }
+
+        let mut kept = vec![false; report.len()];
+        let mut current = Some(last_kept);
+        while let Some(i) = current {
+            kept[i] = true;
+            current = previous_kept[i];
+        }
+        best = Some((0..report.len()).filter(|&i| !kept[i]).collect());
+    }
+    best
 }
 
 fn load_input() -> Vec<Vec<i32>> {
@@ -127,14 +352,78 @@ fn load_input() -> Vec<Vec<i32>> {
     if !path.exists() {
         panic!("File not found: {:?}", path.to_str());
     }
-    let file = File::open(&path).expect("Failed to open file");
-    let reader = io::BufReader::new(file);
+    let input = fs::read_to_string(path).expect("Failed to open file");
 
-    let list_of_reports = reader.lines().map(|line| {
-        let line = line.expect("Failed to read line");
-        line.split_whitespace()
-            .map(|num| num.parse::<i32>().expect("Failed to parse number"))
-            .collect::<Vec<i32>>()
+    let list_of_reports = input.lines().map(|line| {
+        parse::integers(line).expect("Failed to parse number")
     }).collect::<Vec<Vec<i32>>>();
     list_of_reports
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(dampener_budget: usize) -> SafetyPolicy {
+        SafetyPolicy { dampener_budget, ..SafetyPolicy::default() }
+    }
+
+    fn without(report: &[i32], removed: &[usize]) -> Vec<i32> {
+        report.iter().enumerate().filter(|(i, _)| !removed.contains(i)).map(|(_, &level)| level).collect()
+    }
+
+    #[test]
+    fn removes_a_bad_first_level() {
+        assert_eq!(dampened_removals(&[9, 1, 2, 3, 4], &SafetyPolicy::part_2()), Some(vec![0]));
+        assert_eq!(dampened_removals(&[1, 5, 4, 3, 2], &SafetyPolicy::part_2()), Some(vec![0]));
+    }
+
+    #[test]
+    fn budget_of_two_removes_two_levels() {
+        let report = [1, 2, 9, 9, 3, 4];
+        assert_eq!(dampened_removals(&report, &SafetyPolicy::part_2()), None);
+        assert_eq!(dampened_removals(&report, &budget(2)), Some(vec![2, 3]));
+        assert_eq!(dampened_removals(&[1, 2, 3, 4], &budget(2)), Some(vec![]));
+    }
+
+    #[test]
+    fn returned_indices_leave_a_safe_report() {
+        assert_eq!(dampened_removals(&[1, 2, 7, 3, 4], &SafetyPolicy::part_2()), Some(vec![2]));
+        assert_eq!(dampened_removals(&[7, 6, 4, 2, 1, 9], &SafetyPolicy::part_2()), Some(vec![5]));
+
+        // Every report of 6 levels from a small alphabet, against trying every set of removals
+        let alphabet = [1, 2, 4, 7];
+        for code in 0..alphabet.len().pow(6) {
+            let report: Vec<i32> = (0..6).map(|digit| alphabet[code / alphabet.len().pow(digit) % alphabet.len()]).collect();
+            for k in 0..=2 {
+                let policy = budget(k);
+                let fewest = (0..1usize << report.len())
+                    .filter(|mask| (mask.count_ones() as usize) <= k)
+                    .filter(|mask| {
+                        let removed: Vec<usize> = (0..report.len()).filter(|i| mask & (1 << i) != 0).collect();
+                        first_violation(&without(&report, &removed), &policy).is_none()
+                    })
+                    .map(|mask| mask.count_ones() as usize)
+                    .min();
+                let removed = dampened_removals(&report, &policy);
+                assert_eq!(removed.as_ref().map(Vec::len), fewest, "{:?} with budget {}", report, k);
+                if let Some(removed) = removed {
+                    assert_eq!(first_violation(&without(&report, &removed), &policy), None, "{:?} without {:?}", report, removed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parses_a_policy_and_rejects_huge_dampener_budgets() {
+        let policy: SafetyPolicy = "wide: max=5, dampener=2".parse().unwrap();
+        assert_eq!(policy, SafetyPolicy { name: "wide".to_string(), max_step: 5, dampener_budget: 2, ..SafetyPolicy::default() });
+        assert_eq!("big: dampener=101".parse::<SafetyPolicy>(), Err("Policy 'big' can remove at most 100 levels".to_string()));
+        assert!(format!("huge: dampener={}", usize::MAX).parse::<SafetyPolicy>().is_err());
+    }
+
+    #[test]
+    fn unbounded_budget_doesnt_overflow() {
+        assert_eq!(dampened_removals(&[1, 9, 2, 3], &budget(usize::MAX)), Some(vec![1]));
+    }
+}
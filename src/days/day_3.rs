@@ -1,66 +1,428 @@
+use std::fmt;
 use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::Path;
 use log::{debug, info};
+use crate::utils::checked;
+use crate::utils::csv;
+use crate::utils::render;
+use crate::utils::render::Colour;
 
-const VALID_PROGRAM_REGEX: &str = r"mul\((\d{1,3}),(\d{1,3})\)";
-const DISABLE_KEYWORD: &str = "don't()";
-const ENABLE_KEYWORD: &str = "do()";
+// Arguments are 1 to 3 digit numbers, mul(1234,5) is corrupted
+const MAX_DIGITS: usize = 3;
+// How much of a memory dump is read at a time when streaming
+const CHUNK_SIZE: usize = 64 * 1024;
 
-pub fn part_1() {
-    let program = match fs::read_to_string("./resources/day3.txt") {
-        Ok(program) => program,
-        Err(_) => panic!("Failed to read file")
-    };
-    debug!("program: {}", program);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(i64, i64),
+    Do,
+    Dont,
+    // Anything registered with Interpreter::register
+    Custom { name: &'static str, args: Vec<i64> },
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Mul(lhs, rhs) => write!(f, "mul({},{})", lhs, rhs),
+            Instruction::Do => write!(f, "do()"),
+            Instruction::Dont => write!(f, "don't()"),
+            Instruction::Custom { name, args } => {
+                let args: Vec<String> = args.iter().map(i64::to_string).collect();
+                write!(f, "{}({})", name, args.join(","))
+            }
+        }
+    }
+}
+
+// An instruction found in the program, offset and length are in bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub(crate) offset: usize,
+    pub(crate) length: usize,
+    pub(crate) instruction: Instruction,
+}
+
+// What the lexer looks for: name(arg,arg,...) with exactly arity arguments
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub(crate) name: &'static str,
+    pub(crate) arity: usize,
+}
+
+impl Signature {
+    // name(999,999,...) is as long as a call can get
+    fn max_length(&self) -> usize {
+        self.name.len() + 2 + self.arity * MAX_DIGITS + self.arity.saturating_sub(1)
+    }
+}
 
-    let regex = regex::Regex::new(VALID_PROGRAM_REGEX).unwrap();
-    let matches = regex.captures_iter(&program);
+const BUILT_IN: [Signature; 3] = [
+    Signature { name: "mul", arity: 2 },
+    Signature { name: "do", arity: 0 },
+    Signature { name: "don't", arity: 0 },
+];
 
-    let mut result = 0;
-    matches.for_each(|m| {
-        let lhs: i32 = m.get(1).unwrap().as_str().parse().expect("Failed to parse lhs");
-        let rhs: i32 = m.get(2).unwrap().as_str().parse().expect("Failed to parse rhs");
+/*
+    Single pass over the corrupted memory, read chunk by chunk so dumps of any size fit.
+    At every byte each signature is tried in turn, and nothing may sit between the name,
+    the brackets, the commas and the digits. A match is skipped over whole, anything else
+    moves the scan on by one byte.
 
-        result += lhs * rhs;
-    });
+    Only the unscanned tail of the input is kept, and the scan stops short of its end until
+    the longest possible call fits in what's buffered, so a call cut in half by a chunk
+    boundary is finished with the next chunk. A program already in memory can be lexed
+    through its bytes, &[u8] is a BufRead too.
+ */
+pub struct Lexer<R: BufRead> {
+    reader: R,
+    signatures: Vec<Signature>,
+    lookahead: usize,
+    buffer: Vec<u8>,
+    position: usize,
+    // Offset in the whole input of buffer[0]
+    consumed: usize,
+    at_end: bool,
+}
+
+impl<R: BufRead> Lexer<R> {
+    pub fn new(reader: R, signatures: Vec<Signature>) -> Self {
+        let lookahead = signatures.iter().map(Signature::max_length).max().unwrap_or(0);
+        Self { reader, signatures, lookahead, buffer: Vec::new(), position: 0, consumed: 0, at_end: false }
+    }
+
+    fn read_chunk(&mut self) -> io::Result<()> {
+        self.buffer.drain(..self.position);
+        self.consumed += mem::take(&mut self.position);
+        let chunk = self.reader.fill_buf()?;
+        if chunk.is_empty() {
+            self.at_end = true;
+        }
+        self.buffer.extend_from_slice(chunk);
+        let read = chunk.len();
+        self.reader.consume(read);
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for Lexer<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.position < self.buffer.len() {
+                if !self.at_end && self.buffer.len() - self.position < self.lookahead {
+                    break;
+                }
+                let offset = self.position;
+                if let Some((length, instruction)) = match_any(&self.buffer[offset..], &self.signatures) {
+                    self.position = offset + length;
+                    return Some(Ok(Token { offset: self.consumed + offset, length, instruction }));
+                }
+                self.position += 1;
+            }
+            if self.at_end {
+                return None;
+            }
+            if let Err(error) = self.read_chunk() {
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+// The first signature the input starts with, and how many bytes its call takes
+fn match_any(input: &[u8], signatures: &[Signature]) -> Option<(usize, Instruction)> {
+    signatures.iter().find_map(|signature| {
+        match_signature(input, signature).map(|(length, args)| (length, to_instruction(signature.name, args)))
+    })
+}
+
+// Length of the whole call and its arguments if the input starts with one
+fn match_signature(input: &[u8], signature: &Signature) -> Option<(usize, Vec<i64>)> {
+    if !input.starts_with(signature.name.as_bytes()) {
+        return None;
+    }
+    let mut i = signature.name.len();
+    if input.get(i) != Some(&b'(') {
+        return None;
+    }
+    i += 1;
+    let mut args: Vec<i64> = Vec::with_capacity(signature.arity);
+    for index in 0..signature.arity {
+        if index > 0 {
+            if input.get(i) != Some(&b',') {
+                return None;
+            }
+            i += 1;
+        }
+        let digits = input[i..].iter().take(MAX_DIGITS + 1).take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 || digits > MAX_DIGITS {
+            return None;
+        }
+        args.push(input[i..i + digits].iter().fold(0, |value, digit| value * 10 + (digit - b'0') as i64));
+        i += digits;
+    }
+    if input.get(i) != Some(&b')') {
+        return None;
+    }
+    Some((i + 1, args))
+}
+
+fn to_instruction(name: &'static str, args: Vec<i64>) -> Instruction {
+    match name {
+        "mul" => Instruction::Mul(args[0], args[1]),
+        "do" => Instruction::Do,
+        "don't" => Instruction::Dont,
+        _ => Instruction::Custom { name, args },
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    pub(crate) enabled: bool,
+    pub(crate) total: i64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self { enabled: true, total: 0 }
+    }
+}
+
+// What running one instruction did, enabled is the state it ran in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub(crate) token: Token,
+    pub(crate) enabled: bool,
+    pub(crate) contribution: i64,
+}
+
+pub type Handler = fn(&mut State, &[i64]);
+
+/*
+    Runs instructions against an explicit state.
+    mul only adds to the total while enabled, do() and don't() switch that unless toggles
+    are ignored like in part 1. New instructions only need a handler, the lexer picks them
+    up through signatures():
+
+        Interpreter::new()
+            .register("add", 2, |state, args| if state.enabled { state.total += args[0] + args[1] })
+            .register("reset", 0, |state, _| *state = State::default())
+            .run(program.as_bytes())
+ */
+pub struct Interpreter {
+    state: State,
+    toggles: bool,
+    custom: Vec<(Signature, Handler)>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self { state: State::default(), toggles: true, custom: Vec::new() }
+    }
+
+    pub fn ignoring_toggles(mut self) -> Self {
+        self.toggles = false;
+        self
+    }
+
+    // No day uses an instruction of its own yet, the tests register add and reset
+    #[allow(dead_code)]
+    pub fn register(mut self, name: &'static str, arity: usize, handler: Handler) -> Self {
+        if self.signatures().iter().any(|signature| signature.name == name) {
+            panic!("Instruction {} is already defined", name);
+        }
+        self.custom.push((Signature { name, arity }, handler));
+        self
+    }
+
+    pub fn signatures(&self) -> Vec<Signature> {
+        BUILT_IN.iter().copied().chain(self.custom.iter().map(|(signature, _)| *signature)).collect()
+    }
+
+    pub fn execute(&mut self, instruction: &Instruction) {
+        match instruction {
+            Instruction::Mul(lhs, rhs) => {
+                if self.state.enabled {
+                    self.state.total = checked::add(self.state.total, checked::mul(*lhs, *rhs));
+                }
+            }
+            Instruction::Do if self.toggles => self.state.enabled = true,
+            Instruction::Dont if self.toggles => self.state.enabled = false,
+            Instruction::Do | Instruction::Dont => {}
+            Instruction::Custom { name, args } => {
+                let (_, handler) = self.custom.iter()
+                    .find(|(signature, _)| signature.name == *name)
+                    .unwrap_or_else(|| panic!("Instruction {} was never registered", name));
+                handler(&mut self.state, args);
+            }
+        }
+    }
+
+    // Constant memory however big the input is
+    pub fn run<R: BufRead>(&mut self, reader: R) -> io::Result<i64> {
+        self.trace(reader, |_| {})
+    }
+
+    // Like run, and hands over a record of every instruction as it is executed
+    pub fn trace<R, F>(&mut self, reader: R, mut on_record: F) -> io::Result<i64>
+    where
+        R: BufRead,
+        F: FnMut(Record),
+    {
+        for token in Lexer::new(reader, self.signatures()) {
+            let token = token?;
+            debug!("{}: {:?}", token.offset, token.instruction);
+            let (enabled, total) = (self.state.enabled, self.state.total);
+            self.execute(&token.instruction);
+            on_record(Record { token, enabled, contribution: self.state.total - total });
+        }
+        Ok(self.state.total)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.enabled
+    }
+}
+
+pub fn part_1() {
+    let result = Interpreter::new().ignoring_toggles().run(open_program()).expect("Failed to read file");
     info!("Part 1: {}", result);
 }
 
 pub fn part_2() {
-    let mut program = match fs::read_to_string("./resources/day3.txt") {
-        Ok(program) => program,
+    let result = Interpreter::new().run(open_program()).expect("Failed to read file");
+    info!("Part 2: {}", result);
+}
+
+// The program with every instruction marked, to see which mul calls counted
+pub fn annotate() {
+    let program = fs::read_to_string("./resources/day3.txt").expect("Failed to read file");
+    let mut interpreter = Interpreter::new();
+    let mut records: Vec<Record> = Vec::new();
+    interpreter.trace(program.as_bytes(), |record| records.push(record)).expect("Failed to read file");
+    info!("Annotated program:\n{}", annotated_program(&program, &records, interpreter.is_enabled(), render::colours_supported()));
+}
+
+/*
+    With colours, counted instructions are green, do() and don't() yellow and anything
+    in a disabled region (including the mul calls there) grey.
+    Without them counted instructions are wrapped in [] and every other instruction in {},
+    so the disabled regions are the stretches between a {don't()} and the next {do()}.
+ */
+fn annotated_program(program: &str, records: &[Record], enabled_at_end: bool, colours: bool) -> String {
+    let mut output = String::new();
+    let mut position = 0;
+    for record in records {
+        push_text(&mut output, &program[position..record.token.offset], record.enabled, colours);
+
+        let end = record.token.offset + record.token.length;
+        let text = &program[record.token.offset..end];
+        let counted = match record.token.instruction {
+            Instruction::Mul(..) => record.enabled,
+            _ => record.contribution != 0,
+        };
+        let colour = match record.token.instruction {
+            _ if counted => Colour::Green,
+            Instruction::Do | Instruction::Dont => Colour::Yellow,
+            _ if !record.enabled => Colour::Grey,
+            _ => Colour::Cyan,
+        };
+        match (colours, counted) {
+            (true, _) => output.push_str(&render::paint(text, colour)),
+            (false, true) => output.push_str(&format!("[{}]", text)),
+            (false, false) => output.push_str(&format!("{{{}}}", text)),
+        }
+        position = end;
+    }
+    // Text after the last instruction is in whatever state the program ended in
+    push_text(&mut output, &program[position..], enabled_at_end, colours);
+    output
+}
+
+fn push_text(output: &mut String, text: &str, enabled: bool, colours: bool) {
+    if text.is_empty() || enabled || !colours {
+        output.push_str(text);
+    } else {
+        output.push_str(&render::paint(text, Colour::Grey));
+    }
+}
+
+// offset,instruction,enabled,contribution for every instruction, as CSV
+pub fn export_trace(path: &Path) {
+    let mut writer = BufWriter::new(File::create(path).expect("Failed to create trace file"));
+    writeln!(writer, "offset,instruction,enabled,contribution").expect("Failed to write trace");
+    let mut count = 0;
+    Interpreter::new().trace(open_program(), |record| {
+        writeln!(writer, "{},{},{},{}", record.token.offset, csv::field(&record.token.instruction.to_string()), record.enabled, record.contribution)
+            .expect("Failed to write trace");
+        count += 1;
+    }).expect("Failed to read file");
+    writer.flush().expect("Failed to write trace");
+    info!("Wrote {} instructions to {:?}", count, path);
+}
+
+// Streamed rather than read whole, memory dumps can be far bigger than the puzzle input
+fn open_program() -> BufReader<File> {
+    match File::open("./resources/day3.txt") {
+        Ok(file) => BufReader::with_capacity(CHUNK_SIZE, file),
         Err(_) => panic!("Failed to read file")
-    };
-    debug!("program: {}", program);
-
-    if !program.contains("don't()") {
-        part_1()
-    }
-
-    let regex = regex::Regex::new(VALID_PROGRAM_REGEX).unwrap(); // will always exist
-
-    let mut result = 0;
-    loop {
-        let buckets: Vec<&str> = program.splitn(2,DISABLE_KEYWORD).collect();
-        debug!("bucket 1: {}", buckets[0]);
-        let bucket_before_disable = regex.captures_iter(buckets[0]);
-        bucket_before_disable.for_each(|m| {
-            let lhs: i32 = m.get(1).unwrap().as_str().parse().expect("Failed to parse lhs");
-            let rhs: i32 = m.get(2).unwrap().as_str().parse().expect("Failed to parse rhs");
-            debug!("lhs: {}, rhs: {}", lhs, rhs);
-            result += lhs * rhs;
-        });
-
-        if buckets.len() > 1 && buckets[1].contains(ENABLE_KEYWORD) {
-            let next_bucket: Vec<&str> = buckets[1].splitn(2, ENABLE_KEYWORD).collect();
-            if next_bucket.is_empty(){
-                break;
-            } else {
-                program = next_bucket[1].parse().unwrap(); // unwrap because already checked if exists
-                debug!("nextBlock: {}", program);
-            }
-        } else {
-            break;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMORY: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))\
+        mul(123,4)don't()mmul(999,999)do_not()do()mul(1,999)mul(12,3";
+
+    #[test]
+    fn chunk_boundaries_dont_change_the_result() {
+        let whole_part_1 = Interpreter::new().ignoring_toggles().run(MEMORY.as_bytes()).unwrap();
+        let whole_part_2 = Interpreter::new().run(MEMORY.as_bytes()).unwrap();
+        assert_eq!(whole_part_1, 161 + 492 + 998_001 + 999);
+        assert_eq!(whole_part_2, 48 + 492 + 999);
+
+        for capacity in 1..=16 {
+            let part_1 = Interpreter::new().ignoring_toggles().run(BufReader::with_capacity(capacity, MEMORY.as_bytes())).unwrap();
+            let part_2 = Interpreter::new().run(BufReader::with_capacity(capacity, MEMORY.as_bytes())).unwrap();
+            assert_eq!((part_1, part_2), (whole_part_1, whole_part_2), "buffer of {} bytes", capacity);
         }
     }
-    info!("Part 2: {}", result);
-}
\ No newline at end of file
+
+    fn with_add_and_reset() -> Interpreter {
+        Interpreter::new()
+            .register("add", 2, |state, args| if state.enabled { state.total += args[0] + args[1] })
+            .register("reset", 0, |state, _| *state = State::default())
+    }
+
+    #[test]
+    fn registered_instructions_are_lexed_and_run() {
+        // add(1,2 is cut short and ignored, reset() clears the total, add is skipped while disabled
+        let program = "mul(2,3)add(10,20)xadd(1,2reset()mul(4,5)don't()add(7,7)do()add(1,1)";
+        assert_eq!(with_add_and_reset().run(program.as_bytes()).unwrap(), 22);
+
+        for capacity in 1..=16 {
+            let result = with_add_and_reset().run(BufReader::with_capacity(capacity, program.as_bytes())).unwrap();
+            assert_eq!(result, 22, "buffer of {} bytes", capacity);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Instruction add is already defined")]
+    fn registering_a_name_twice_panics() {
+        with_add_and_reset().register("add", 1, |_, _| {});
+    }
+
+    #[test]
+    #[should_panic(expected = "Instruction mul is already defined")]
+    fn registering_a_built_in_name_panics() {
+        Interpreter::new().register("mul", 2, |_, _| {});
+    }
+}
@@ -1,20 +1,16 @@
 use std::fs;
-use log::info;
+use log::{debug, info};
 use crate::utils::direction::Direction;
-
-const XMAS: [char; 4] = ['X', 'M', 'A', 'S'];
+use crate::utils::parse;
+use crate::utils::word_search::WordSearch;
 
 pub fn part_1() {
     let grid = load_input();
-    let mut christmas_count = 0;
-    for (y, row) in grid.iter().enumerate() {
-        for (x, c) in row.iter().enumerate() {
-            if c == &'X' {
-                christmas_count += search_for_xmas(&grid, x as i32, y as i32);
-            }
-        }
+    let hits = WordSearch::new(&grid).find(&["XMAS"]);
+    for hit in &hits {
+        debug!("{} at {:?} going {:?}", hit.word, hit.start, hit.direction);
     }
-    info!("Christmas count: {} ", christmas_count);
+    info!("Christmas count: {} ", hits.len());
 }
 
 pub fn part_2() {
@@ -30,31 +26,6 @@ pub fn part_2() {
     info!("Real Christmas count: {} ", christmas_count);
 }
 
-fn search_for_xmas(grid: &Vec<Vec<char>>, x: i32, y: i32) -> i32 {
-    let mut count = 0;
-    for direction in Direction::ALL_DIRECTIONS {
-        if directional_dfs(grid, 0, &direction, x, y) {
-            count += 1;
-        }
-    }
-
-    count
-}
-
-fn directional_dfs(grid: &Vec<Vec<char>>, current_letter_index: usize, direction: &Direction, x: i32, y: i32) -> bool {
-    if y < 0 || y > grid.len() as i32 -1 || x < 0 || x > grid.get(0).unwrap().len() as i32 -1 {
-        return false
-    }
-    if current_letter_index == 3 && grid[y as usize][x as usize] == XMAS[3] {
-        true
-    } else if grid[y as usize][x as usize] == XMAS[current_letter_index] {
-        let (new_x, new_y) = direction.apply_movement(x, y);
-        directional_dfs(grid, current_letter_index + 1, direction, new_x, new_y)
-    } else {
-        false
-    }
-}
-
 fn search_for_real_xmas(grid: &Vec<Vec<char>>, x: i32, y: i32) -> i32{
     let nw = Direction::NORTH_WEST.apply_movement(x, y);
     let ne = Direction::NORTH_EAST.apply_movement(x, y);
@@ -97,16 +68,5 @@ fn load_input() -> Vec<Vec<char>> {
         Ok(input) => input,
         Err(_) => panic!("Failed to read file")
     };
-
-    let len_y: usize = input.lines().count();
-    let len_x: usize = input.lines().next().unwrap().chars().count();
-
-    let mut grid: Vec<Vec<char>> = vec![vec!['.'; len_x]; len_y];
-
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            grid[y][x] = c;
-        }
-    }
-    grid
+    parse::char_grid(&input).expect("Failed to parse grid")
 }
\ No newline at end of file
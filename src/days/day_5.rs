@@ -1,6 +1,8 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use log::{debug, info};
+use crate::utils::checked;
+use crate::utils::parse;
 
 pub fn part_1_and_2() {
     let (dependency_map, updates) = load_input();
@@ -111,41 +113,32 @@ fn prepend_dependencies_recursively(page: i32, required_dependencies: &HashMap<i
 
 fn load_input() -> (HashMap<i32, HashSet<i32>>, Vec<Vec<i32>>) {
     let mut dependency_map: HashMap<i32, HashSet<i32>> = HashMap::new();
-    let mut updates: Vec<Vec<i32>> = Vec::new();
 
     let input = match fs::read_to_string("./resources/day5.txt") {
         Ok(input) => input,
         Err(_) => panic!("Failed to read file")
     };
-    for line in input.lines() {
-        if line.is_empty() {
-            continue;
-        }
-        if line.contains('|') {
-            let parts: Vec<&str> = line.split('|').collect();
-            let x: i32 = parts[0].parse().unwrap();
-            let y: i32 = parts[1].parse().unwrap();
-            let dependency_set = dependency_map.entry(y).or_insert_with(HashSet::new);
-            dependency_set.insert(x);
-        }
-        if line.contains(',') {
-            let pages: Vec<i32> = line.split(',')
-                .map(|s| {
-                    s.trim().parse().unwrap()
-                })
-                .collect();
-            updates.push(pages);
-        }
+    let sections = parse::sections(&input);
+    let (rules, pages) = match sections.as_slice() {
+        [rules, pages] => (rules, pages),
+        _ => panic!("Expected a rules section and an updates section")
     };
+
+    let rules: Vec<[i32; 2]> = parse::records(rules).expect("Failed to parse rules");
+    for [x, y] in rules {
+        dependency_map.entry(y).or_default().insert(x);
+    }
+    let updates: Vec<Vec<i32>> = pages.lines()
+        .map(|line| parse::integers(line).expect("Failed to parse update"))
+        .collect();
     // info!("Updates: {:?}", updates);
     (dependency_map, updates)
 }
 
 fn print_middle_sum(updates: &Vec<Vec<i32>>) {
-    let middle_sum: i32 = updates.iter().filter_map(|valid_update| {
-        return valid_update.get(valid_update.len()/2);
-    })
-        .sum();
+    let middle_sum: i32 = checked::sum(updates.iter().filter_map(|valid_update| {
+        valid_update.get(valid_update.len()/2).copied()
+    }));
     info!("Middle Sum: {}", middle_sum);
 }
 
@@ -1,11 +1,19 @@
 use std::collections::HashSet;
 use std::fs;
+use std::path::Path;
 use log::{debug, info};
+use crate::utils::asciicast::Recorder;
+use crate::utils::cycle;
 use crate::utils::direction::Direction;
+use crate::utils::grid::Bounds;
+use crate::utils::image::{FrameSequence, Image, ImageFormat, Palette, Rgb, BLACK, WHITE};
+use crate::utils::parse;
 use crate::utils::position::Position;
+use crate::utils::render;
+use crate::utils::render::{Colour, Overlay, Renderer};
 
 const CHARACTER_SHAPES: [char; 4] = ['v', '^', '<', '>'];
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 struct Character {
     direction: Direction,
     position: Position<usize>,
@@ -60,13 +68,20 @@ impl Character {
 const EMPTY_SHAPE: char = '.';
 const VISITED_SHAPE: char = 'o';
 const BLOCKING_SHAPE: char = '#';
+const DEBUG_VIEW_RADIUS_X: i64 = 40;
+const DEBUG_VIEW_RADIUS_Y: i64 = 20;
+const FRAME_SCALE: usize = 2;
+const VISITED_COLOUR: Rgb = [96, 96, 96];
+const GUARD_COLOUR: Rgb = [255, 0, 0];
 
 pub fn part_1() {
     let (mut grid, mut character) = load_input();
+    let mut recorder = Recorder::start("day6_part1").expect("Failed to start recording");
 
     let mut count = 0;
     loop {
-        debug!("{}", grid_to_string(&grid, &character));
+        recorder.step(|| grid_to_string(&grid, &character, true)).expect("Failed to record step");
+        debug!("\n{}", grid_to_string(&grid, &character, render::colours_supported()));
         debug!("Player Location: {:?}", character.position);
 
         if grid[character.position.y][character.position.x] == EMPTY_SHAPE {
@@ -90,6 +105,7 @@ pub fn part_1() {
             _ => panic!("Unknown shape detected")
         }
     }
+    recorder.finish(|| grid_to_string(&grid, &character, true)).expect("Failed to finish recording");
     info!("Part 1: {}", count);
 }
 
@@ -103,7 +119,7 @@ pub fn part_2() {
     let (mut grid, mut character) = load_input();
     let mut valid_blockers: HashSet<(usize, usize)> = HashSet::new();
     loop {
-        debug!("{}", grid_to_string(&grid, &character));
+        debug!("\n{}", grid_to_string(&grid, &character, render::colours_supported()));
         grid[character.position.y][character.position.x] = VISITED_SHAPE;
 
         let (x, y) = match character.get_next_position(&grid) {
@@ -114,7 +130,7 @@ pub fn part_2() {
         if grid[y][x] != BLOCKING_SHAPE && grid[y][x] != VISITED_SHAPE {
             let original_shape = grid[y][x];
             grid[y][x] = BLOCKING_SHAPE;
-            if has_loop(&grid, character.clone()) {
+            if has_loop(&grid, character) {
                 debug!("Blocker at x:{}, y:{} creates a loop", x, y);
                 valid_blockers.insert((x, y));
             }
@@ -137,30 +153,41 @@ pub fn part_2() {
     info!("Part 2: {}", valid_blockers.len());
 }
 
-fn has_loop(grid: &Vec<Vec<char>>, mut character: Character) -> bool {
-    let mut index = 0;
-    let mut tail = character.clone();
+// A frame every time the guard turns plus one of the whole path, for following the walk outside the terminal
+pub fn export_guard_frames(directory: &Path) {
+    let (mut grid, mut character) = load_input();
+    let palette = Palette::new(BLACK).with(BLOCKING_SHAPE, WHITE).with(VISITED_SHAPE, VISITED_COLOUR);
+    let draw = |grid: &Vec<Vec<char>>, character: &Character| {
+        let mut image = Image::from_grid(grid, &palette, FRAME_SCALE);
+        image.fill_cell(character.position.x, character.position.y, FRAME_SCALE, GUARD_COLOUR);
+        image
+    };
+    let mut frames = FrameSequence::new(directory, "day6_guard", ImageFormat::Png).expect("Failed to create frame directory");
     loop {
-        let (x_1, y_1) = match handle_character_movement(&grid, &mut character) {
-            None => return false,
-            Some((x, y)) => (x,y)
+        grid[character.position.y][character.position.x] = VISITED_SHAPE;
+        let (x, y) = match character.get_next_position(&grid) {
+            None => break,
+            Some((x, y)) => (x, y)
         };
-        character.position.x = x_1;
-        character.position.y = y_1;
-
-        if index % 2 == 1 {
-            let (x_2, y_2) = match handle_character_movement(&grid, &mut tail) {
-                None => return false,
-                Some((x, y)) => (x,y)
-            };
-            tail.position.x = x_2;
-            tail.position.y = y_2;
-            if tail.position == character.position && tail.direction == character.direction {
-                return true;
-            }
+        if grid[y][x] == BLOCKING_SHAPE {
+            frames.write_frame(&draw(&grid, &character)).expect("Failed to write frame");
+            character.rotate_clockwise();
+        } else {
+            character.position = Position::new(x, y);
         }
-        index += 1;
     }
+    frames.write_frame(&draw(&grid, &character)).expect("Failed to write frame");
+    info!("Wrote {} frames of the guard's path to {:?}", frames.frames_written(), directory);
+}
+
+fn has_loop(grid: &Vec<Vec<char>>, character: Character) -> bool {
+    cycle::brent(&character, |current| {
+        let mut next = *current;
+        let (x, y) = handle_character_movement(grid, &mut next)?;
+        next.position.x = x;
+        next.position.y = y;
+        Some(next)
+    }).is_some()
 }
 
 fn handle_character_movement(grid: &Vec<Vec<char>>, character: &mut Character) -> Option<(usize, usize)> {
@@ -180,53 +207,42 @@ fn handle_character_movement(grid: &Vec<Vec<char>>, character: &mut Character) -
     }
 }
 
-fn grid_to_string(grid: &Vec<Vec<char>>, character: &Character) -> String {
-    let mut result = String::new();
-    for (y, row) in grid.iter().enumerate() {
-        let mut line = String::new();
-        for (x, &element) in row.iter().enumerate() {
-            if y == character.position.y && x == character.position.x {
-                line.push(character.get_shape());
-            } else {
-                line.push(element);
-            }
-        }
-        result.push('\n');
-        result.push_str(&line);
-        result.push('\n');
-    }
-    result
+// Large maps are cropped to a window around the guard
+fn grid_to_string(grid: &Vec<Vec<char>>, character: &Character, colours: bool) -> String {
+    let guard = Position::new(character.position.x as i64, character.position.y as i64);
+    let visited: Vec<Position<i64>> = grid.iter().enumerate().flat_map(|(y, row)| {
+        row.iter().enumerate()
+            .filter(|(_, &c)| c == VISITED_SHAPE)
+            .map(move |(x, _)| Position::new(x as i64, y as i64))
+    }).collect();
+    let viewport = Bounds {
+        min: Position::new(guard.x - DEBUG_VIEW_RADIUS_X, guard.y - DEBUG_VIEW_RADIUS_Y),
+        max: Position::new(guard.x + DEBUG_VIEW_RADIUS_X, guard.y + DEBUG_VIEW_RADIUS_Y),
+    };
+    Renderer::new(grid, |c| *c)
+        .overlay(Overlay::new("visited", visited).symbol(VISITED_SHAPE).colour(Colour::Grey))
+        .overlay(Overlay::new("guard", [guard]).symbol(character.get_shape()).colour(Colour::Red))
+        .colours(colours)
+        .legend(true)
+        .viewport(viewport)
+        .render()
 }
 
 fn load_input() -> (Vec<Vec<char>>, Character) {
-    let mut starting_location: Option<Position<usize>> = None;
-    let mut starting_shape: Option<char> = None;
-
-    let grid: Vec<Vec<char>> = match fs::read_to_string("./resources/day6.txt") {
+    let input = match fs::read_to_string("./resources/day6.txt") {
         Err(_) => panic!("Could not load file"),
-        Ok(input) =>{
-            input
-                .lines()
-                .enumerate()
-                .map(|(y, line)| {
-                    return line
-                        .chars()
-                        .enumerate()
-                        .map(|(x, c)| {
-                            if CHARACTER_SHAPES.contains(&c) {
-                                starting_location = Some(Position::new(x, y));
-                                starting_shape = Some(c);
-                                return EMPTY_SHAPE
-                            }
-                            c
-                        })
-                        .collect()
-                })
-                .collect()
-        }
+        Ok(input) => input
     };
-    match (starting_location, starting_shape) {
-        (Some(location), Some(shape)) => (grid, Character::new(location, shape)),
-        _ => panic!("Could not find starting location or shape"),
+    let mut grid = parse::char_grid(&input).expect("Could not parse map");
+
+    for (y, row) in grid.iter_mut().enumerate() {
+        for (x, c) in row.iter_mut().enumerate() {
+            if CHARACTER_SHAPES.contains(c) {
+                let character = Character::new(Position::new(x, y), *c);
+                *c = EMPTY_SHAPE;
+                return (grid, character);
+            }
+        }
     }
+    panic!("Could not find starting location or shape")
 }
\ No newline at end of file
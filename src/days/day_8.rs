@@ -1,7 +1,10 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use log::{debug, info};
+use crate::utils::parse;
 use crate::utils::position::Position;
+use crate::utils::render;
+use crate::utils::render::{Colour, Overlay, Renderer};
 
 pub fn part_1() {
     // The pattern seems to be sum(x = n*(n+1))
@@ -83,46 +86,49 @@ fn is_out_of_bounds(position: &Position<i32>, boundary: &Position<i32>) -> bool
 
 // Debug function
 fn to_grid_string(antenna_map: &HashMap<char, HashSet<Position<i32>>>, antinode_set: &HashSet<(i32, i32)>, boundary: &Position<i32>) -> String {
-    let mut output = String::new();
-    for y in 0..boundary.y {
-        for x in 0..boundary.x {
-            let mut found = false;
-            for (c, locations) in antenna_map {
-                if locations.contains(&Position::new(x, y)) {
-                    if antinode_set.contains(&(x, y)) {
-                        output.push('X')
-                    } else {
-                        output.push(*c);
-                    }
-                    found = true;
-                    break;
-                }
-            }
-            if !found && antinode_set.contains(&(x, y)) {
-                output.push('#');
-            } else if !found {
-                output.push('.');
-            }
-        }
-        output.push('\n');
+    let grid: Vec<Vec<char>> = vec![vec!['.'; boundary.x as usize]; boundary.y as usize];
+    let antinodes: HashSet<Position<i64>> = antinode_set.iter()
+        .map(|(x, y)| Position::new(*x as i64, *y as i64))
+        .collect();
+
+    let mut renderer = Renderer::new(&grid, |c| *c)
+        .overlay(Overlay::new("antinode", antinodes.iter().copied()).symbol('#'))
+        .colours(render::colours_supported())
+        .legend(true);
+    let mut frequencies: Vec<&char> = antenna_map.keys().collect();
+    frequencies.sort();
+    for (i, c) in frequencies.into_iter().enumerate() {
+        let antennas: Vec<Position<i64>> = antenna_map[c].iter()
+            .map(|location| Position::new(location.x as i64, location.y as i64))
+            .collect();
+        let overlapping: Vec<Position<i64>> = antennas.iter()
+            .filter(|location| antinodes.contains(location))
+            .copied()
+            .collect();
+        renderer = renderer
+            .overlay(Overlay::new(&format!("antenna {}", c), antennas).symbol(*c).colour(Colour::nth(i)))
+            .overlay(Overlay::new(&format!("antenna {} on an antinode", c), overlapping).symbol('X').colour(Colour::nth(i)));
     }
-    debug!("Count grid shows: {}", output.matches('#').count());
+    let output = renderer.render();
+    let count: usize = output.lines().take(boundary.y as usize).map(|line| line.matches('#').count()).sum();
+    debug!("Count grid shows: {}", count);
     output
 }
 
 pub fn load_input() -> (HashMap<char, HashSet<Position<i32>>>, Position<i32>) {
     let input = fs::read_to_string("./resources/day8.txt")
         .expect("Failed to read input");
-    let grid_boundary: Position<i32> = Position::new(input.lines().next().unwrap().len() as i32, input.lines().count() as i32);
+    let grid = parse::char_grid(&input).expect("Failed to parse grid");
+    let grid_boundary: Position<i32> = Position::new(grid[0].len() as i32, grid.len() as i32);
 
     let mut map_of_antennas: HashMap<char, HashSet<Position<i32>>> = HashMap::new();
-    input.lines().enumerate()
-        .for_each(|(y, line)| {
-            line.chars()
+    grid.iter().enumerate()
+        .for_each(|(y, row)| {
+            row.iter()
                 .enumerate().for_each(|(x, c)| {
                 if c.is_alphanumeric() {
-                    map_of_antennas.entry(c)
-                        .or_insert_with(HashSet::new)
+                    map_of_antennas.entry(*c)
+                        .or_default()
                         .insert(Position::new(x as i32, y as i32));
                 }
             })
@@ -1,6 +1,12 @@
 use std::collections::{VecDeque};
 use std::fs;
 use log::{debug, info};
+use crate::utils::asciicast::Recorder;
+use crate::utils::checked;
+use crate::utils::intervals::RangeSet;
+use crate::utils::position::Position;
+use crate::utils::render;
+use crate::utils::render::{Colour, Overlay, Renderer};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum BlockType {
@@ -98,108 +104,79 @@ pub fn part_1() {
 
     let mut hash: u64 = 0;
     for (index, id) in result_list.iter().enumerate() {
-        hash += index as u64 * *id as u64;
+        hash = checked::add(hash, checked::mul(index as u64, *id as u64));
     }
     info!("Part 1: {}", hash);
 }
 
 /*
-    This is... terrible, don't look at it.
+    Used space is kept as a RangeSet so the leftmost gap that fits a file is a single lookup.
+    Files still carry their own start position since neighbouring files merge into one span.
  */
 pub fn part_2() {
     let input = fs::read_to_string("./resources/day9.txt")
         .expect("Failed to load file");
     debug!("Input: {}", input);
-    let mut block_list: Vec<Block> = Vec::new();
-    let mut file_index = 0;
-    for (index, c) in input.chars().enumerate() {
+    let mut used_space = RangeSet::new();
+    let mut file_list: Vec<(Block, i64)> = Vec::new();
+    let mut position: i64 = 0;
+    for (index, c) in input.trim_end().chars().enumerate() {
+        let length = c.to_digit(10).expect("char was not a number") as i32;
         if index % 2 == 0 {
-            block_list.push(Block::new(
-                BlockType::FileBlock,
-                file_index,
-                c.to_digit(10).expect("char was not a number") as i32));
-            file_index += 1;
-        } else {
-            block_list.push(Block::new(
-                BlockType::EmptyBlock,
-                file_index, // Add index so that we know if we can insert a file here
-                c.to_digit(10).expect("char was not a number") as i32));
+            file_list.push((Block::new(BlockType::FileBlock, (index / 2) as i32, length), position));
+            used_space.insert(position..position + length as i64);
         }
+        position += length as i64;
     }
-    debug!("Original Block list: {:?}", block_list);
-
-    let mut end_index = block_list.len();
-    while end_index > 0 {
-        end_index -= 1;
-        debug!("{}", get_block_string(&block_list));
-        if block_list.get(end_index).is_some() {
-            if block_list.get(end_index).unwrap().block_type == BlockType::EmptyBlock {
-                continue
-            }
-        } else {
-            break;
-        }
-        debug!("End index: {}", end_index);
+    debug!("Original file list: {:?}", file_list);
 
-        for i in 0..block_list.len(){
-            let block = &block_list[i];
-            let curr_end_file = &block_list[end_index];
-            if block.block_type == BlockType::FileBlock {
-                continue
-            }
-            debug!("Comparing: {:?} to {:?}", block, curr_end_file);
-            if block.length >= curr_end_file.length && i < end_index {
-                debug!("Moving {}", curr_end_file.id);
-                if block.length == curr_end_file.length {
-                    block_list.swap(i, end_index);
-                } else {
-                    let new_empty_block = Block{
-                        block_type: BlockType::EmptyBlock,
-                        id: block_list[i].id,
-                        length: curr_end_file.length,
-                    };
-                    block_list[i].length = block_list[i].length - curr_end_file.length;
-                    block_list.insert(i, new_empty_block);
-                    block_list.swap(i, end_index+1);
-                }
-                break;
-            }
+    let mut recorder = Recorder::start("day9_part2").expect("Failed to start recording");
+    for file_index in (0..file_list.len()).rev() {
+        debug!("{}", get_block_string(&file_list, file_index, render::colours_supported()));
+        recorder.step(|| get_block_string(&file_list, file_index, true)).expect("Failed to record step");
+        let (file, start) = &mut file_list[file_index];
+        let length = file.length as i64;
+        let gap = used_space.first_gap(0, length);
+        if gap < *start {
+            debug!("Moving {} from {} to {}", file.id, start, gap);
+            used_space.remove(*start..*start + length);
+            used_space.insert(gap..gap + length);
+            *start = gap;
         }
     }
 
+    recorder.finish(|| get_block_string(&file_list, 0, true)).expect("Failed to finish recording");
+
     let mut hash: u64 = 0;
-    let mut index = 0;
-    for block in &block_list {
-        if block.block_type == BlockType::EmptyBlock {
-            index += block.length;
-        } else {
-            for i in index..(block.length+index) {
-                hash += block.id as u64 * i as u64;
-            }
-            index += block.length;
+    for (file, start) in &file_list {
+        for i in *start..(*start + file.length as i64) {
+            hash = checked::add(hash, checked::mul(file.id as u64, i as u64));
         }
     }
 
     info!("Part 2: {}", hash);
 }
 
-fn get_block_string(block_list: & Vec<Block>) -> String {
-    let mut index = 0;
-    let mut print_string = String::new();
-    for block in block_list {
-        if block.block_type == BlockType::EmptyBlock {
-            for _ in index..(block.length + index) {
-                print_string.push_str(".");
-            }
-            index += block.length;
-        } else {
-            for _ in index..(block.length + index) {
-                print_string.push_str(&format!("{}", block.id));
-            }
-            index += block.length;
+// File ids are shown by their last digit, the file being moved is highlighted
+fn get_block_string(file_list: &[(Block, i64)], current_file: usize, colours: bool) -> String {
+    let disk_length = file_list.iter().map(|(file, start)| start + file.length as i64).max().unwrap_or(0);
+    let mut disk: Vec<Vec<Option<i32>>> = vec![vec![None; disk_length as usize]];
+    for (file, start) in file_list {
+        for i in *start..(*start + file.length as i64) {
+            disk[0][i as usize] = Some(file.id);
         }
     }
-    print_string
+    let moving: Vec<Position<i64>> = match file_list.get(current_file) {
+        None => Vec::new(),
+        Some((file, start)) => (*start..(*start + file.length as i64)).map(|x| Position::new(x, 0)).collect()
+    };
+    Renderer::new(&disk, |cell| match cell {
+        None => '.',
+        Some(id) => char::from_digit((id % 10) as u32, 10).unwrap(),
+    })
+        .overlay(Overlay::new("moving", moving).colour(Colour::Yellow))
+        .colours(colours)
+        .render()
 }
 
 // fn repeat_char(key: String, length: i32) -> String {
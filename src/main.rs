@@ -1,85 +1,140 @@
 use std::env;
+use std::path::{Path, PathBuf};
 use env_logger::Env;
 use log::info;
+use crate::days::day_1::{ListMode, PairingCost};
+use crate::days::day_2::SafetyPolicy;
+use crate::utils::asciicast;
+use crate::utils::asciicast::RecordingOptions;
+use crate::utils::checked;
 
 mod days;
 mod utils;
 
 fn main() {
     env_logger::init_from_env(Env::default().default_filter_or("info"));
+    if let Some(options) = get_recording_options() {
+        asciicast::enable(options);
+    }
 
     for &day in &get_days_to_run() {
         match day {
             1 => {
                 info!("Advent of Code 2024!");
                 info!("Day 1:");
-                days::day_1::part_1();
-                days::day_1::part_2();
+                let list_mode = get_list_mode();
+                match get_external_merge_chunk_size() {
+                    Some(chunk_size) => {
+                        // The merge only streams sorted values, it can't pair with another cost or break the distance down
+                        if get_pairing_cost().is_some() || env::args().any(|arg| arg == "--breakdown") {
+                            panic!("--external-merge can't be combined with --pairing-cost or --breakdown");
+                        }
+                        run_part(1, "1 and 2", || days::day_1::part_1_and_2_external(chunk_size, list_mode));
+                    }
+                    None => {
+                        match get_pairing_cost() {
+                            Some(cost) => run_part(1, "1", || days::day_1::part_1_with_cost(cost, list_mode)),
+                            None => run_part(1, "1", || days::day_1::part_1(list_mode)),
+                        }
+                        run_part(1, "2", || days::day_1::part_2(list_mode));
+                        if env::args().any(|arg| arg == "--breakdown") {
+                            run_part(1, "breakdown", || days::day_1::breakdown(list_mode));
+                        }
+                    }
+                }
             }
             2 => {
                 info!("Day 2:");
-                days::day_2::part_1();
-                days::day_2::part_2();
+                run_part(2, "1", days::day_2::part_1);
+                run_part(2, "2", days::day_2::part_2);
+                let policies = get_safety_policies();
+                if !policies.is_empty() {
+                    run_part(2, "policies", || days::day_2::evaluate_policies(&policies));
+                }
+                if let Some(path) = get_diagnostics_path() {
+                    let all_policies: Vec<SafetyPolicy> = [SafetyPolicy::default(), SafetyPolicy::part_2()].into_iter()
+                        .chain(policies)
+                        .collect();
+                    run_part(2, "diagnostics", || days::day_2::export_diagnostics(&path, &all_policies));
+                }
             }
             3 => {
                 info!("Day 3:");
-                days::day_3::part_1();
-                days::day_3::part_2();
+                run_part(3, "1", days::day_3::part_1);
+                run_part(3, "2", days::day_3::part_2);
+                if env::args().any(|arg| arg == "--annotate") {
+                    run_part(3, "annotated", days::day_3::annotate);
+                }
+                if let Some(path) = get_trace_path() {
+                    run_part(3, "trace", || days::day_3::export_trace(&path));
+                }
             }
             4 => {
                 info!("Day 4:");
-                days::day_4::part_1();
-                days::day_4::part_2();
+                run_part(4, "1", days::day_4::part_1);
+                run_part(4, "2", days::day_4::part_2);
             }
             5 => {
                 info!("Day 5:");
-                days::day_5::part_1_and_2();
+                run_part(5, "1 and 2", days::day_5::part_1_and_2);
             }
             6 => {
                 info!("Day 6:");
-                days::day_6::part_1();
-                days::day_6::part_2();
+                run_part(6, "1", days::day_6::part_1);
+                run_part(6, "2", days::day_6::part_2);
+                if let Some(directory) = get_image_directory() {
+                    run_part(6, "frames", || days::day_6::export_guard_frames(&directory));
+                }
             }
             7 => {
                 info!("Day 7:");
-                days::day_7::part_1();
-                days::day_7::part_2();
+                run_part(7, "1", days::day_7::part_1);
+                run_part(7, "2", days::day_7::part_2);
             }
             8 => {
                 info!("Day 8:");
-                days::day_8::part_1();
-                days::day_8::part_2();
+                run_part(8, "1", days::day_8::part_1);
+                run_part(8, "2", days::day_8::part_2);
             }
             9 => {
                 info!("Day 9:");
-                days::day_9::part_1();
-                days::day_9::part_2();
+                run_part(9, "1", days::day_9::part_1);
+                run_part(9, "2", days::day_9::part_2);
             }
             10 => {
                 info!("Day 10:");
-                days::day_10::part_1();
-                days::day_10::part_2();
+                run_part(10, "1", days::day_10::part_1);
+                run_part(10, "2", days::day_10::part_2);
             }
             11 => {
                 info!("Day 11:");
-                days::day_11::part_1();
-                days::day_11::part_2();
+                run_part(11, "1", days::day_11::part_1);
+                run_part(11, "2", days::day_11::part_2);
             }
             12 => {
                 info!("Day 12:");
-                days::day_12::part_1();
-                days::day_12::part_2();
+                run_part(12, "1", days::day_12::part_1);
+                run_part(12, "2", days::day_12::part_2);
+                if let Some(directory) = get_image_directory() {
+                    run_part(12, "image", || days::day_12::export_region_image(&directory));
+                }
             }
             13 => {
                 info!("Day 13:");
-                days::day_13::part_1();
-                days::day_13::part_2();
+                run_part(13, "1", days::day_13::part_1);
+                run_part(13, "2", days::day_13::part_2);
             }
             _ => {}
         }
     }
 }
 
+// Lets overflow reports from utils::checked say which solver they came from
+fn run_part(day: i32, part: &'static str, solver: impl FnOnce()) {
+    checked::set_context(day, part);
+    solver();
+}
+
 fn get_days_to_run() -> Vec<i32> {
     let args: Vec<i32> = env::args().skip(1).filter_map(|arg| arg.parse().ok()).collect();
 
@@ -88,4 +143,74 @@ fn get_days_to_run() -> Vec<i32> {
         [start] => vec![*start],
         _ => (1..=25).collect(),
     }
-}
\ No newline at end of file
+}
+
+// --record=<dir> turns on asciicast recording, --frame-delay=<ms> and --sample=<n> tune it
+fn get_recording_options() -> Option<RecordingOptions> {
+    let mut directory: Option<PathBuf> = None;
+    let mut frame_delay_ms = 100;
+    let mut sample_every = 1;
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("--record=") {
+            directory = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--frame-delay=") {
+            frame_delay_ms = value.parse().expect("--frame-delay takes a number of milliseconds");
+        } else if let Some(value) = arg.strip_prefix("--sample=") {
+            sample_every = value.parse().expect("--sample takes a number of steps");
+        }
+    }
+    if sample_every == 0 {
+        panic!("--sample must be at least 1");
+    }
+    directory.map(|directory| RecordingOptions { directory, frame_delay_ms, sample_every })
+}
+
+// --external-merge=<chunk size> runs day 1 over sorted runs on disk instead of in memory
+fn get_external_merge_chunk_size() -> Option<usize> {
+    let value = env::args().skip(1).find_map(|arg| arg.strip_prefix("--external-merge=").map(str::to_string))?;
+    match value.parse() {
+        Ok(chunk_size) if chunk_size > 0 => Some(chunk_size),
+        _ => panic!("--external-merge takes a chunk size of at least 1"),
+    }
+}
+
+// --pairing-cost=<absolute|squared|capped:n> swaps day 1's distance for another pairing cost
+fn get_pairing_cost() -> Option<PairingCost> {
+    let value = env::args().skip(1).find_map(|arg| arg.strip_prefix("--pairing-cost=").map(str::to_string))?;
+    Some(value.parse().unwrap_or_else(|error: String| panic!("{}", error)))
+}
+
+// --lists=lenient lets day 1 accept lines with a single location, the default is strict
+fn get_list_mode() -> ListMode {
+    env::args().skip(1)
+        .find_map(|arg| arg.strip_prefix("--lists=").map(str::to_string))
+        .map_or(ListMode::Strict, |value| value.parse().unwrap_or_else(|error: String| panic!("{}", error)))
+}
+
+// Every --policy=<policy> and every policy in a --policy-file=<path>, in the order given
+fn get_safety_policies() -> Vec<SafetyPolicy> {
+    let mut policies = Vec::new();
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("--policy=") {
+            policies.push(value.parse().unwrap_or_else(|error: String| panic!("{}", error)));
+        } else if let Some(value) = arg.strip_prefix("--policy-file=") {
+            policies.extend(days::day_2::load_policies(Path::new(value)).unwrap_or_else(|error| panic!("{}", error)));
+        }
+    }
+    policies
+}
+
+// --diagnostics=<path> writes why each day 2 report is or isn't safe to a CSV file
+fn get_diagnostics_path() -> Option<PathBuf> {
+    env::args().skip(1).find_map(|arg| arg.strip_prefix("--diagnostics=").map(PathBuf::from))
+}
+
+// --trace=<path> writes every day 3 instruction with its state and contribution to a CSV file
+fn get_trace_path() -> Option<PathBuf> {
+    env::args().skip(1).find_map(|arg| arg.strip_prefix("--trace=").map(PathBuf::from))
+}
+
+// --image=<dir> writes a frame sequence of day 6's guard and a picture of day 12's regions into that directory
+fn get_image_directory() -> Option<PathBuf> {
+    env::args().skip(1).find_map(|arg| arg.strip_prefix("--image=").map(PathBuf::from))
+}
@@ -0,0 +1,218 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use log::info;
+
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+#[derive(Debug, Clone)]
+pub struct RecordingOptions {
+    pub(crate) directory: PathBuf,
+    pub(crate) frame_delay_ms: u64,
+    pub(crate) sample_every: usize,
+}
+
+static OPTIONS: OnceLock<RecordingOptions> = OnceLock::new();
+
+// Set once by the runner, recording stays off when this is never called
+pub fn enable(options: RecordingOptions) {
+    OPTIONS.set(options).expect("Recording was already enabled");
+}
+
+/*
+    Writes the frames of a simulation to <directory>/<name>.cast as an asciinema v2 recording,
+    which can be replayed with `asciinema play`. The terminal size comes from the first frame.
+    When recording is disabled every call is a no-op, so days can record unconditionally.
+ */
+pub struct Recorder {
+    output: Option<CastWriter>,
+}
+
+struct CastWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    options: RecordingOptions,
+    step: usize,
+    frames: usize,
+}
+
+impl Recorder {
+    pub fn start(name: &str) -> io::Result<Recorder> {
+        match OPTIONS.get() {
+            None => Ok(Recorder { output: None }),
+            Some(options) => Recorder::with_options(name, options.clone())
+        }
+    }
+
+    fn with_options(name: &str, options: RecordingOptions) -> io::Result<Recorder> {
+        fs::create_dir_all(&options.directory)?;
+        let path = options.directory.join(format!("{}.cast", name));
+        let writer = BufWriter::new(File::create(&path)?);
+        Ok(Recorder { output: Some(CastWriter { path, writer, options, step: 0, frames: 0 }) })
+    }
+
+    // Only every sample_every-th step is rendered, the closure isn't called for the others
+    pub fn step<F: FnOnce() -> String>(&mut self, render: F) -> io::Result<()> {
+        let output = match self.output.as_mut() {
+            None => return Ok(()),
+            Some(output) => output
+        };
+        let is_sampled = output.step % output.options.sample_every == 0;
+        output.step += 1;
+        if is_sampled {
+            output.write_frame(&render())?;
+        }
+        Ok(())
+    }
+
+    // The end state is always recorded, unless the last step was sampled and already shows it
+    pub fn finish<F: FnOnce() -> String>(self, render: F) -> io::Result<()> {
+        let mut output = match self.output {
+            None => return Ok(()),
+            Some(output) => output
+        };
+        let last_step_sampled = output.step > 0 && (output.step - 1) % output.options.sample_every == 0;
+        if !last_step_sampled {
+            output.write_frame(&render())?;
+        }
+        output.writer.flush()?;
+        info!("Recorded {} frames to {:?}", output.frames, output.path);
+        Ok(())
+    }
+}
+
+impl CastWriter {
+    fn write_frame(&mut self, frame: &str) -> io::Result<()> {
+        if self.frames == 0 {
+            let width = frame.lines().map(visible_width).max().unwrap_or(0).max(1);
+            let height = frame.lines().count().max(1);
+            writeln!(self.writer, "{{\"version\": 2, \"width\": {}, \"height\": {}}}", width, height)?;
+        }
+        let time = (self.frames as u64 * self.options.frame_delay_ms) as f64 / 1000.0;
+        let data = format!("{}{}", CLEAR_SCREEN, frame.trim_end_matches('\n').replace('\n', "\r\n"));
+        writeln!(self.writer, "[{:.3}, \"o\", \"{}\"]", time, escape_json(&data))?;
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+// Characters on screen, ignoring ANSI colour sequences
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in line.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => width += 1,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+        }
+    }
+    width
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn options(name: &str, sample_every: usize) -> RecordingOptions {
+        let directory = env::temp_dir().join(format!("aoc-asciicast-{}-{}", name, process::id()));
+        RecordingOptions { directory, frame_delay_ms: 100, sample_every }
+    }
+
+    // Frames written for a run of the given number of steps, not counting the header line
+    fn frames_recorded(name: &str, steps: usize, sample_every: usize) -> usize {
+        let options = options(name, sample_every);
+        let mut recorder = Recorder::with_options(name, options.clone()).unwrap();
+        for step in 0..steps {
+            recorder.step(|| format!("step {}", step)).unwrap();
+        }
+        recorder.finish(|| "done".to_string()).unwrap();
+
+        let path = options.directory.join(format!("{}.cast", name));
+        let frames = fs::read_to_string(&path).unwrap().lines().count() - 1;
+        fs::remove_dir_all(&options.directory).unwrap();
+        frames
+    }
+
+    #[test]
+    fn escape_json_escapes_quotes_backslashes_and_control_chars() {
+        assert_eq!(escape_json("plain"), "plain");
+        assert_eq!(escape_json("say \"hi\""), "say \\\"hi\\\"");
+        assert_eq!(escape_json("a\\b"), "a\\\\b");
+        assert_eq!(escape_json("\r\n\t"), "\\r\\n\\t");
+        assert_eq!(escape_json("\x1b[31m"), "\\u001b[31m");
+    }
+
+    #[test]
+    fn visible_width_ignores_colour_sequences() {
+        assert_eq!(visible_width("abc"), 3);
+        assert_eq!(visible_width("\x1b[31ma\x1b[0mbc"), 3);
+        assert_eq!(visible_width("\x1b[90m\x1b[0m"), 0);
+    }
+
+    #[test]
+    fn only_every_nth_step_is_rendered() {
+        let options = options("sampling", 3);
+        let mut recorder = Recorder::with_options("sampling", options.clone()).unwrap();
+        let mut rendered = Vec::new();
+        for step in 0..7 {
+            recorder.step(|| {
+                rendered.push(step);
+                String::new()
+            }).unwrap();
+        }
+        recorder.finish(String::new).unwrap();
+        fs::remove_dir_all(&options.directory).unwrap();
+        assert_eq!(rendered, vec![0, 3, 6]);
+    }
+
+    #[test]
+    fn final_frame_is_only_added_when_the_last_step_was_skipped() {
+        // Steps 0, 3 and 6 are sampled, the last of 7 steps is one of them
+        assert_eq!(frames_recorded("last-sampled", 7, 3), 3);
+        // Step 7 was skipped so the end state gets a frame of its own
+        assert_eq!(frames_recorded("last-skipped", 8, 3), 4);
+        assert_eq!(frames_recorded("every-step", 5, 1), 5);
+        assert_eq!(frames_recorded("no-steps", 0, 3), 1);
+    }
+
+    #[test]
+    fn header_and_frames_are_valid_lines() {
+        let options = options("format", 1);
+        let mut recorder = Recorder::with_options("format", options.clone()).unwrap();
+        recorder.step(|| "ab\n\x1b[31mc\x1b[0m\n".to_string()).unwrap();
+        recorder.step(|| "\"q\"".to_string()).unwrap();
+        recorder.finish(String::new).unwrap();
+
+        let contents = fs::read_to_string(options.directory.join("format.cast")).unwrap();
+        fs::remove_dir_all(&options.directory).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines, vec![
+            "{\"version\": 2, \"width\": 2, \"height\": 2}",
+            "[0.000, \"o\", \"\\u001b[H\\u001b[2Jab\\r\\n\\u001b[31mc\\u001b[0m\"]",
+            "[0.100, \"o\", \"\\u001b[H\\u001b[2J\\\"q\\\"\"]",
+        ]);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::utils::checked;
+
+/*
+    Minimum cost assignment of rows to columns, the Hungarian algorithm with potentials in O(n^2 m).
+    Needs at least as many columns as rows and gives every row a different column.
+    Returns the total cost and, for every row, the column it was assigned to.
+ */
+pub fn hungarian(costs: &[Vec<i64>]) -> (i64, Vec<usize>) {
+    let rows = costs.len();
+    if rows == 0 {
+        return (0, Vec::new());
+    }
+    let columns = costs[0].len();
+    if costs.iter().any(|row| row.len() != columns) {
+        panic!("Every row of the cost matrix needs the same number of columns");
+    }
+    if columns < rows {
+        panic!("Can't assign {} rows to only {} columns", rows, columns);
+    }
+
+    /*
+        Everything is 1-based so that column 0 can stand for "not on the path yet".
+        row_potential and column_potential keep every reduced cost non-negative,
+        matched_row[column] is the row using that column and previous[column] walks the
+        augmenting path back once a free column has been reached.
+     */
+    let mut row_potential = vec![0i64; rows + 1];
+    let mut column_potential = vec![0i64; columns + 1];
+    let mut matched_row = vec![0usize; columns + 1];
+    let mut previous = vec![0usize; columns + 1];
+
+    for row in 1..=rows {
+        matched_row[0] = row;
+        let mut current_column = 0;
+        let mut min_slack = vec![i64::MAX; columns + 1];
+        let mut visited = vec![false; columns + 1];
+        loop {
+            visited[current_column] = true;
+            let current_row = matched_row[current_column];
+            let mut delta = i64::MAX;
+            let mut next_column = 0;
+            for column in 1..=columns {
+                if visited[column] {
+                    continue;
+                }
+                let reduced = costs[current_row - 1][column - 1] - row_potential[current_row] - column_potential[column];
+                if reduced < min_slack[column] {
+                    min_slack[column] = reduced;
+                    previous[column] = current_column;
+                }
+                if min_slack[column] < delta {
+                    delta = min_slack[column];
+                    next_column = column;
+                }
+            }
+            for column in 0..=columns {
+                if visited[column] {
+                    row_potential[matched_row[column]] += delta;
+                    column_potential[column] -= delta;
+                } else {
+                    min_slack[column] -= delta;
+                }
+            }
+            current_column = next_column;
+            if matched_row[current_column] == 0 {
+                break;
+            }
+        }
+        // Flip the augmenting path so the new row gets a column
+        while current_column != 0 {
+            let previous_column = previous[current_column];
+            matched_row[current_column] = matched_row[previous_column];
+            current_column = previous_column;
+        }
+    }
+
+    let mut assignment = vec![0usize; rows];
+    for column in 1..=columns {
+        if matched_row[column] != 0 {
+            assignment[matched_row[column] - 1] = column - 1;
+        }
+    }
+    let total = checked::sum(assignment.iter().enumerate().map(|(row, &column)| costs[row][column]));
+    (total, assignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheapest total over every way of giving each row its own column
+    fn brute_force(costs: &[Vec<i64>]) -> i64 {
+        fn assign(costs: &[Vec<i64>], row: usize, used: &mut [bool]) -> i64 {
+            if row == costs.len() {
+                return 0;
+            }
+            let mut best = i64::MAX;
+            for column in 0..used.len() {
+                if !used[column] {
+                    used[column] = true;
+                    best = best.min(costs[row][column] + assign(costs, row + 1, used));
+                    used[column] = false;
+                }
+            }
+            best
+        }
+        assign(costs, 0, &mut vec![false; costs[0].len()])
+    }
+
+    // Small deterministic pseudo-random costs, negatives included
+    fn matrix(rows: usize, columns: usize, seed: u64) -> Vec<Vec<i64>> {
+        let mut state = seed;
+        (0..rows).map(|_| (0..columns).map(|_| {
+            state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) as i64 % 41 - 10
+        }).collect()).collect()
+    }
+
+    fn check(costs: &[Vec<i64>]) {
+        let (total, assignment) = hungarian(costs);
+        assert_eq!(total, brute_force(costs), "{:?}", costs);
+        let mut columns = assignment.clone();
+        columns.sort_unstable();
+        columns.dedup();
+        assert_eq!(columns.len(), costs.len(), "columns reused in {:?}", assignment);
+        assert_eq!(total, assignment.iter().enumerate().map(|(row, &column)| costs[row][column]).sum::<i64>());
+    }
+
+    #[test]
+    fn matches_brute_force_on_square_matrices() {
+        check(&[vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]]);
+        for size in 1..=6 {
+            for seed in 0..20 {
+                check(&matrix(size, size, seed));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_with_more_columns_than_rows() {
+        let (total, assignment) = hungarian(&[vec![7, 1, 9, 2], vec![8, 1, 9, 3]]);
+        assert_eq!((total, assignment), (3, vec![3, 1]));
+        for rows in 1..=4 {
+            for columns in rows + 1..=6 {
+                for seed in 0..10 {
+                    check(&matrix(rows, columns, seed));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_matrix_costs_nothing() {
+        assert_eq!(hungarian(&[]), (0, Vec::new()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't assign")]
+    fn more_rows_than_columns_panics() {
+        hungarian(&[vec![1], vec![2]]);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-arithmetic")]
+    #[should_panic(expected = "Overflow")]
+    fn total_past_i64_overflows() {
+        let half = i64::MAX / 2 + 1;
+        hungarian(&[vec![half, half], vec![half, half]]);
+    }
+}
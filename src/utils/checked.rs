@@ -0,0 +1,95 @@
+use std::fmt::Display;
+use std::sync::Mutex;
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, Zero};
+
+/*
+    Arithmetic for answers that could outgrow their type.
+    Built with the checked-arithmetic feature every operation is checked and an overflow panics
+    with the day, part and operation that caused it. Without the feature these are the plain
+    operators, so release builds keep their speed (and their silent wrapping).
+ */
+
+// (day, part) of the solver that is running, set by the runner
+static CONTEXT: Mutex<Option<(i32, &str)>> = Mutex::new(None);
+
+pub fn set_context(day: i32, part: &'static str) {
+    *CONTEXT.lock().unwrap() = Some((day, part));
+}
+
+pub fn add<T: CheckedAdd + Display>(a: T, b: T) -> T {
+    if cfg!(feature = "checked-arithmetic") {
+        match a.checked_add(&b) {
+            Some(result) => result,
+            None => overflow("+", &a, &b),
+        }
+    } else {
+        a + b
+    }
+}
+
+pub fn sub<T: CheckedSub + Display>(a: T, b: T) -> T {
+    if cfg!(feature = "checked-arithmetic") {
+        match a.checked_sub(&b) {
+            Some(result) => result,
+            None => overflow("-", &a, &b),
+        }
+    } else {
+        a - b
+    }
+}
+
+pub fn mul<T: CheckedMul + Display>(a: T, b: T) -> T {
+    if cfg!(feature = "checked-arithmetic") {
+        match a.checked_mul(&b) {
+            Some(result) => result,
+            None => overflow("*", &a, &b),
+        }
+    } else {
+        a * b
+    }
+}
+
+// Negatives go through 0 - a, so the one value without a positive counterpart is caught too
+pub fn abs<T: CheckedSub + Zero + PartialOrd + Display>(a: T) -> T {
+    if a < T::zero() {
+        sub(T::zero(), a)
+    } else {
+        a
+    }
+}
+
+pub fn sum<T, I>(values: I) -> T
+where
+    T: CheckedAdd + Zero + Display,
+    I: IntoIterator<Item = T>,
+{
+    values.into_iter().fold(T::zero(), add)
+}
+
+fn overflow<T: Display>(operation: &str, a: &T, b: &T) -> ! {
+    // Copied out so the lock isn't held, and poisoned, by the panic below
+    let context = *CONTEXT.lock().unwrap();
+    match context {
+        Some((day, part)) => panic!("Overflow in day {} part {}: {} {} {}", day, part, a, operation, b),
+        None => panic!("Overflow: {} {} {}", a, operation, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abs_flips_negatives_only() {
+        assert_eq!(abs(-5i32), 5);
+        assert_eq!(abs(7i64), 7);
+        assert_eq!(abs(0i32), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-arithmetic")]
+    #[should_panic(expected = "Overflow")]
+    fn abs_of_the_most_negative_value_overflows() {
+        abs(i32::MIN);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::hash_map;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::AddAssign;
+use num_traits::{One, Zero};
+
+// Anything that can be counted with, from usize up to BigUint
+pub trait Count: Clone + Ord + Zero + One + AddAssign {}
+
+impl<T: Clone + Ord + Zero + One + AddAssign> Count for T {}
+
+// Multiset of keys, missing keys have a count of zero
+#[derive(Debug, Clone)]
+pub struct Counter<K, N = usize> {
+    counts: HashMap<K, N>,
+}
+
+impl<K, N> Default for Counter<K, N> {
+    fn default() -> Self {
+        Self { counts: HashMap::new() }
+    }
+}
+
+impl<K, N> Counter<K, N>
+where
+    K: Eq + Hash,
+    N: Count,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, key: K) {
+        self.add_n(key, N::one());
+    }
+
+    pub fn add_n(&mut self, key: K, n: N) {
+        *self.counts.entry(key).or_insert_with(N::zero) += n;
+    }
+
+    pub fn get(&self, key: &K) -> N {
+        self.counts.get(key).cloned().unwrap_or_else(N::zero)
+    }
+
+    // Sum of every count, not the number of distinct keys
+    pub fn total(&self) -> N {
+        let mut total = N::zero();
+        for count in self.counts.values() {
+            total += count.clone();
+        }
+        total
+    }
+
+    // Number of distinct keys
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    // The k keys with the highest counts, highest first. Ties come out in no particular order.
+    pub fn most_common(&self, k: usize) -> Vec<(&K, &N)> {
+        let mut entries: Vec<(&K, &N)> = self.counts.iter().collect();
+        entries.sort_by(|(_, a), (_, b)| b.cmp(a));
+        entries.truncate(k);
+        entries
+    }
+
+    pub fn merge(&mut self, other: Counter<K, N>) {
+        for (key, count) in other.counts {
+            self.add_n(key, count);
+        }
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, K, N> {
+        self.counts.iter()
+    }
+}
+
+impl<K: Eq + Hash, N: Count> FromIterator<K> for Counter<K, N> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<K: Eq + Hash, N: Count> Extend<K> for Counter<K, N> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.add(key);
+        }
+    }
+}
+
+impl<K, N> IntoIterator for Counter<K, N> {
+    type Item = (K, N);
+    type IntoIter = hash_map::IntoIter<K, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+impl<'a, K, N> IntoIterator for &'a Counter<K, N> {
+    type Item = (&'a K, &'a N);
+    type IntoIter = hash_map::Iter<'a, K, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_common_comes_highest_first() {
+        let counter: Counter<char> = "abcabaa".chars().collect();
+        assert_eq!(counter.most_common(2), vec![(&'a', &4), (&'b', &2)]);
+        assert_eq!(counter.most_common(10).len(), 3);
+        assert!(counter.most_common(0).is_empty());
+    }
+
+    #[test]
+    fn merge_adds_the_counts() {
+        let mut counter: Counter<&str, u64> = Counter::new();
+        counter.add_n("left", 3);
+        let mut other: Counter<&str, u64> = Counter::new();
+        other.add_n("left", 2);
+        other.add("right");
+        counter.merge(other);
+        assert_eq!(counter.get(&"left"), 5);
+        assert_eq!(counter.get(&"right"), 1);
+        assert_eq!(counter.get(&"missing"), 0);
+        assert_eq!(counter.total(), 6);
+    }
+}
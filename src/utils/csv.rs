@@ -0,0 +1,27 @@
+// Quotes a CSV field only when it holds something that would break the row
+pub fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_is_left_alone() {
+        assert_eq!(field("part 1"), "part 1");
+        assert_eq!(field(""), "");
+    }
+
+    #[test]
+    fn quotes_fields_that_would_break_the_row() {
+        assert_eq!(field("a,b"), "\"a,b\"");
+        assert_eq!(field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(field("crlf\r\n"), "\"crlf\r\n\"");
+    }
+}
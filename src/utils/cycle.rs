@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/*
+    A sequence x0, f(x0), f(f(x0)), ... over a finite state space must eventually repeat.
+    start is the index of the first state that is part of the cycle (mu),
+    length is how many steps it takes to come back around (lambda).
+
+    Step functions return None when the simulation ends on its own (e.g. the guard
+    walks off the map), in which case there is no cycle to find.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub(crate) start: usize,
+    pub(crate) length: usize,
+}
+
+impl Cycle {
+    // Maps any step count onto the earliest step that lands on the same state
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+// Tortoise and hare, constant memory
+// Day 6 uses brent, this stays as the reference it's checked against
+#[allow(dead_code)]
+pub fn floyd<S, F>(initial: &S, step: F) -> Option<Cycle>
+where
+    S: Clone + Eq,
+    F: Fn(&S) -> Option<S>,
+{
+    let mut tortoise = step(initial)?;
+    let mut hare = step(&step(initial)?)?;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&step(&hare)?)?;
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise)?;
+    while tortoise != hare {
+        hare = step(&hare)?;
+        length += 1;
+    }
+    Some(Cycle { start, length })
+}
+
+// Teleporting tortoise, constant memory and fewer step calls than floyd
+pub fn brent<S, F>(initial: &S, step: F) -> Option<Cycle>
+where
+    S: Clone + Eq,
+    F: Fn(&S) -> Option<S>,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial)?;
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare)?;
+        length += 1;
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare)?;
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        start += 1;
+    }
+    Some(Cycle { start, length })
+}
+
+// Remembers every state, so it finds the cycle in exactly start + length steps
+// For step functions too expensive to call twice per state, no day has one yet
+#[allow(dead_code)]
+pub fn hashed<S, F>(initial: &S, step: F) -> Option<Cycle>
+where
+    S: Clone + Eq + Hash,
+    F: Fn(&S) -> Option<S>,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut state = initial.clone();
+    let mut index = 0;
+    loop {
+        if let Some(&first_seen) = seen.get(&state) {
+            return Some(Cycle { start: first_seen, length: index - first_seen });
+        }
+        let next = step(&state)?;
+        seen.insert(state, index);
+        state = next;
+        index += 1;
+    }
+}
+
+/*
+    State after n steps without simulating all of them.
+    Returns None if the simulation ends before reaching n steps.
+ */
+// Day 6 only needs to know that there is a cycle, not where it leads
+#[allow(dead_code)]
+pub fn fast_forward<S, F>(initial: &S, n: usize, step: F) -> Option<S>
+where
+    S: Clone + Eq,
+    F: Fn(&S) -> Option<S>,
+{
+    let remaining = match brent(initial, &step) {
+        Some(cycle) => cycle.reduce(n),
+        None => n,
+    };
+    let mut state = initial.clone();
+    for _ in 0..remaining {
+        state = step(&state)?;
+    }
+    Some(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0, 1, 2, 3, 4, 5, 6, 7, 3, 4, ... starts its cycle at index 3 and comes back after 5 steps
+    fn step(n: &u32) -> Option<u32> {
+        Some(if *n == 7 { 3 } else { n + 1 })
+    }
+
+    #[test]
+    fn all_finders_agree() {
+        let expected = Some(Cycle { start: 3, length: 5 });
+        assert_eq!(floyd(&0, step), expected);
+        assert_eq!(brent(&0, step), expected);
+        assert_eq!(hashed(&0, step), expected);
+    }
+
+    #[test]
+    fn ending_simulations_have_no_cycle() {
+        let walk_off = |n: &u32| if *n < 10 { Some(n + 1) } else { None };
+        assert_eq!(floyd(&0, walk_off), None);
+        assert_eq!(brent(&0, walk_off), None);
+        assert_eq!(hashed(&0, walk_off), None);
+    }
+
+    #[test]
+    fn fast_forward_skips_whole_cycles() {
+        assert_eq!(fast_forward(&0, 2, step), Some(2));
+        assert_eq!(fast_forward(&0, 1_000_000_006, step), Some(6));
+        assert_eq!(fast_forward(&0, 11, |n: &u32| if *n < 10 { Some(n + 1) } else { None }), None);
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::Display;
+use num_traits::{CheckedAdd, CheckedMul, ToPrimitive, Unsigned};
+use crate::utils::checked;
+
+/*
+    Base 10 digit helpers that never go through a String.
+    Anything unsigned that can be built from a u8 works: u64, u128 and BigUint.
+ */
+pub trait Digits: Clone + Ord + Unsigned + From<u8> + ToPrimitive + CheckedAdd + CheckedMul + Display {}
+
+impl<T: Clone + Ord + Unsigned + From<u8> + ToPrimitive + CheckedAdd + CheckedMul + Display> Digits for T {}
+
+fn ten<T: Digits>() -> T {
+    T::from(10)
+}
+
+pub fn pow10<T: Digits>(exponent: u32) -> T {
+    let mut result = T::one();
+    for _ in 0..exponent {
+        result = checked::mul(result, ten());
+    }
+    result
+}
+
+// 0 still takes one digit to write down
+pub fn digit_count<T: Digits>(n: &T) -> u32 {
+    let mut count = 1;
+    let mut remaining = n.clone() / ten();
+    while !remaining.is_zero() {
+        remaining = remaining / ten();
+        count += 1;
+    }
+    count
+}
+
+/*
+    Splits the written number in the middle, e.g. 1234 -> (12, 34) and 1000 -> (10, 0).
+    With an odd digit count the extra digit goes to the right half, 12345 -> (12, 345).
+ */
+pub fn split_at_half<T: Digits>(n: &T) -> (T, T) {
+    let count = digit_count(n);
+    let divisor: T = pow10(count - count / 2);
+    (n.clone() / divisor.clone(), n.clone() % divisor)
+}
+
+// Writes y after x, concat(12, 345) = 12345 and concat(12, 0) = 120
+pub fn concat<T: Digits>(x: &T, y: &T) -> T {
+    checked::add(checked::mul(x.clone(), pow10(digit_count(y))), y.clone())
+}
+
+// Leading zeros of the result are dropped, reverse(120) = 21
+// No day reads its numbers backwards yet
+#[allow(dead_code)]
+pub fn reverse<T: Digits>(n: &T) -> T {
+    let mut remaining = n.clone();
+    let mut result = T::zero();
+    while !remaining.is_zero() {
+        result = result * ten() + remaining.clone() % ten();
+        remaining = remaining / ten();
+    }
+    result
+}
+
+// Most significant digit first
+// No day walks the digits one at a time yet
+#[allow(dead_code)]
+pub fn digits<T: Digits>(n: &T) -> impl Iterator<Item = u8> {
+    let mut remaining = n.clone();
+    let mut divisor: T = pow10(digit_count(n) - 1);
+    std::iter::from_fn(move || {
+        if divisor.is_zero() {
+            return None;
+        }
+        let digit = remaining.clone() / divisor.clone();
+        remaining = remaining.clone() % divisor.clone();
+        divisor = divisor.clone() / ten();
+        digit.to_u8()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn reverse_drops_leading_zeros() {
+        assert_eq!(reverse(&120u64), 21);
+        assert_eq!(reverse(&0u64), 0);
+        assert_eq!(reverse(&BigUint::from(12345u32)), BigUint::from(54321u32));
+    }
+
+    #[test]
+    fn digits_come_most_significant_first() {
+        assert_eq!(digits(&1203u64).collect::<Vec<u8>>(), vec![1, 2, 0, 3]);
+        assert_eq!(digits(&0u64).collect::<Vec<u8>>(), vec![0]);
+    }
+
+    #[test]
+    fn split_and_concat_are_inverses() {
+        assert_eq!(split_at_half(&1000u64), (10, 0));
+        assert_eq!(split_at_half(&12345u64), (12, 345));
+        assert_eq!(concat(&12u64, &345u64), 12345);
+        assert_eq!(concat(&12u64, &0u64), 120);
+    }
+
+    #[test]
+    #[cfg(feature = "checked-arithmetic")]
+    #[should_panic(expected = "Overflow")]
+    fn pow10_past_the_type_overflows() {
+        pow10::<u32>(10);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::utils::position::Position;
+
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub struct Direction {
     x: i32,
@@ -5,9 +7,15 @@ pub struct Direction {
 }
 
 impl Direction {
+    pub(crate) const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
     pub(crate) fn apply_movement(&self, x: i32, y: i32) -> (i32, i32) {
         ((x + self.x), (y + self.y))
     }
+    pub(crate) fn step(&self, position: &Position<i64>) -> Position<i64> {
+        Position::new(position.x + self.x as i64, position.y + self.y as i64)
+    }
     pub(crate) const NORTH: Self = Self { x: 0, y: -1 };
     pub(crate) const SOUTH: Self = Self { x: 0, y: 1 };
     pub(crate) const WEST: Self = Self { x: -1, y: 0 };
@@ -18,6 +26,13 @@ impl Direction {
     pub(crate) const SOUTH_EAST: Self = Self { x: 1, y: 1 };
     pub(crate) const SOUTH_WEST: Self = Self { x: -1, y: 1 };
 
+    pub(crate) const CARDINAL_DIRECTIONS: [Direction; 4] = [
+        Direction::NORTH,
+        Direction::SOUTH,
+        Direction::WEST,
+        Direction::EAST,
+    ];
+
     pub(crate) const ALL_DIRECTIONS: [Direction; 8] = [
         Direction::NORTH,
         Direction::SOUTH,
@@ -28,4 +43,15 @@ impl Direction {
         Direction::SOUTH_EAST,
         Direction::SOUTH_WEST,
     ];
+
+    pub(crate) const KNIGHT_MOVES: [Direction; 8] = [
+        Direction::new(1, -2),
+        Direction::new(2, -1),
+        Direction::new(2, 1),
+        Direction::new(1, 2),
+        Direction::new(-1, 2),
+        Direction::new(-2, 1),
+        Direction::new(-2, -1),
+        Direction::new(-1, -2),
+    ];
 }
\ No newline at end of file
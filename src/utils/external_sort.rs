@@ -0,0 +1,238 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_SORTER: AtomicUsize = AtomicUsize::new(0);
+
+// Open run files per merge, well below the usual limit of 1024 open files even with a few sorters side by side
+const DEFAULT_FAN_IN: usize = 64;
+
+/*
+    Sorts more values than fit in memory.
+    Values are buffered until chunk_size of them have arrived, then the chunk is sorted and
+    spilled to a run file in the temp directory. Merging the runs streams every value back in
+    order while holding only one value per run in memory, and can be done as often as needed.
+    At most fan_in runs are open at once, when there are more they are first merged in groups
+    into longer runs until few enough are left.
+    The run files are removed when the sorter is dropped.
+ */
+pub struct ExternalSorter {
+    directory: PathBuf,
+    chunk_size: usize,
+    fan_in: usize,
+    buffer: Vec<i64>,
+    runs: Vec<PathBuf>,
+    next_run: usize,
+    len: usize,
+}
+
+impl ExternalSorter {
+    pub fn new(chunk_size: usize) -> io::Result<Self> {
+        if chunk_size == 0 {
+            panic!("Chunk size must be at least 1");
+        }
+        let name = format!("aoc-external-sort-{}-{}", process::id(), NEXT_SORTER.fetch_add(1, Ordering::Relaxed));
+        let directory = env::temp_dir().join(name);
+        fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            chunk_size,
+            fan_in: DEFAULT_FAN_IN,
+            buffer: Vec::with_capacity(chunk_size),
+            runs: Vec::new(),
+            next_run: 0,
+            len: 0,
+        })
+    }
+
+    // Day 1 merges with the default, a smaller fan-in is for exercising the merge passes
+    #[allow(dead_code)]
+    pub fn fan_in(mut self, fan_in: usize) -> Self {
+        if fan_in < 2 {
+            panic!("Fan-in must be at least 2");
+        }
+        self.fan_in = fan_in;
+        self
+    }
+
+    pub fn push(&mut self, value: i64) -> io::Result<()> {
+        self.buffer.push(value);
+        self.len += 1;
+        if self.buffer.len() >= self.chunk_size {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    // Every value pushed so far, including the ones still in the buffer
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Goes with len, day 1 only reports the count
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    // Spills whatever is still buffered, then merges all the runs
+    pub fn sorted(&mut self) -> io::Result<Merge> {
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+        self.reduce_runs()?;
+        Merge::new(&self.runs)
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        self.buffer.sort_unstable();
+        let path = self.next_run_path();
+        write_run(&path, self.buffer.drain(..).map(Ok))?;
+        self.runs.push(path);
+        Ok(())
+    }
+
+    // Merges the oldest fan_in runs into one new run until the rest can be merged in one go
+    fn reduce_runs(&mut self) -> io::Result<()> {
+        while self.runs.len() > self.fan_in {
+            let group: Vec<PathBuf> = self.runs.drain(..self.fan_in).collect();
+            let path = self.next_run_path();
+            write_run(&path, Merge::new(&group)?)?;
+            for run in &group {
+                fs::remove_file(run)?;
+            }
+            self.runs.push(path);
+        }
+        Ok(())
+    }
+
+    fn next_run_path(&mut self) -> PathBuf {
+        let path = self.directory.join(format!("run_{:05}.bin", self.next_run));
+        self.next_run += 1;
+        path
+    }
+}
+
+fn write_run(path: &Path, values: impl Iterator<Item = io::Result<i64>>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for value in values {
+        writer.write_all(&value?.to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+impl Drop for ExternalSorter {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.directory);
+    }
+}
+
+/*
+    k-way merge over the sorted runs, the heap holds the smallest unread value of every run.
+    A read error doesn't lose the value that was already taken off the heap: that value is
+    returned first, the error on the next call, and after that the merge is over.
+ */
+pub struct Merge {
+    readers: Vec<BufReader<File>>,
+    heap: BinaryHeap<Reverse<(i64, usize)>>,
+    error: Option<io::Error>,
+}
+
+impl Merge {
+    fn new(runs: &[PathBuf]) -> io::Result<Self> {
+        let mut readers: Vec<BufReader<File>> = Vec::with_capacity(runs.len());
+        let mut heap = BinaryHeap::with_capacity(runs.len());
+        for (run, path) in runs.iter().enumerate() {
+            let mut reader = BufReader::new(File::open(path)?);
+            if let Some(value) = read_value(&mut reader)? {
+                heap.push(Reverse((value, run)));
+            }
+            readers.push(reader);
+        }
+        Ok(Self { readers, heap, error: None })
+    }
+}
+
+impl Iterator for Merge {
+    type Item = io::Result<i64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            self.heap.clear();
+            return Some(Err(error));
+        }
+        let Reverse((value, run)) = self.heap.pop()?;
+        match read_value(&mut self.readers[run]) {
+            Ok(Some(next)) => self.heap.push(Reverse((next, run))),
+            Ok(None) => {}
+            Err(error) => self.error = Some(error),
+        }
+        Some(Ok(value))
+    }
+}
+
+fn read_value(reader: &mut impl Read) -> io::Result<Option<i64>> {
+    let mut bytes = [0u8; 8];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(i64::from_le_bytes(bytes))),
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_runs_in_groups_when_there_are_more_than_fan_in() {
+        let mut sorter = ExternalSorter::new(1).unwrap().fan_in(3);
+        let values: Vec<i64> = (0..50).map(|i| (i * 37) % 23 - 11).collect();
+        for &value in &values {
+            sorter.push(value).unwrap();
+        }
+        assert_eq!(sorter.run_count(), 50);
+
+        let merged: Vec<i64> = sorter.sorted().unwrap().map(|value| value.unwrap()).collect();
+        let mut expected = values.clone();
+        expected.sort_unstable();
+        assert_eq!(merged, expected);
+        assert!(sorter.run_count() <= 3);
+        assert_eq!(fs::read_dir(&sorter.directory).unwrap().count(), sorter.run_count());
+
+        // Merging again reads the same reduced runs
+        let again: Vec<i64> = sorter.sorted().unwrap().map(|value| value.unwrap()).collect();
+        assert_eq!(again, expected);
+    }
+
+    #[test]
+    fn read_error_comes_after_the_popped_value_and_ends_the_merge() {
+        let directory = env::temp_dir().join(format!("aoc-merge-error-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        // Reading from a file opened only for writing fails
+        let broken = BufReader::new(File::create(directory.join("broken")).unwrap());
+        fs::write(directory.join("good"), 7i64.to_le_bytes()).unwrap();
+        let good = BufReader::new(File::open(directory.join("good")).unwrap());
+
+        let mut merge = Merge {
+            readers: vec![broken, good],
+            heap: BinaryHeap::from([Reverse((1, 0)), Reverse((2, 1))]),
+            error: None,
+        };
+        assert_eq!(merge.next().unwrap().unwrap(), 1);
+        assert!(merge.next().unwrap().is_err());
+        assert!(merge.next().is_none());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
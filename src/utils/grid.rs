@@ -0,0 +1,108 @@
+use crate::utils::neighbourhood::Neighbourhood;
+use crate::utils::position::Position;
+
+// Inclusive on both corners
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bounds {
+    pub(crate) min: Position<i64>,
+    pub(crate) max: Position<i64>,
+}
+
+impl Bounds {
+    pub fn width(&self) -> i64 {
+        self.max.x - self.min.x + 1
+    }
+
+    pub fn height(&self) -> i64 {
+        self.max.y - self.min.y + 1
+    }
+
+    // Nothing checks positions against a sparse grid's box yet
+    #[allow(dead_code)]
+    pub fn contains(&self, position: &Position<i64>) -> bool {
+        position.x >= self.min.x && position.x <= self.max.x && position.y >= self.min.y && position.y <= self.max.y
+    }
+}
+
+/*
+    Read interface shared by the dense Vec<Vec<T>> grids the days load and SparseGrid,
+    so a solver written against it can switch between the two.
+ */
+pub trait Grid<T> {
+    // None when the position is off a dense grid, a sparse grid answers everywhere
+    fn get(&self, position: &Position<i64>) -> Option<&T>;
+
+    // Smallest box holding every stored cell, None when there are none
+    fn bounds(&self) -> Option<Bounds>;
+
+    // The days walk their dense grids by index, not through the trait
+    #[allow(dead_code)]
+    fn neighbours<'a>(&'a self, position: &Position<i64>, neighbourhood: &'a Neighbourhood) -> impl Iterator<Item = (Position<i64>, &'a T)> + 'a
+    where
+        T: 'a,
+    {
+        neighbourhood.neighbour_positions(position)
+            .filter_map(move |next| self.get(&next).map(|value| (next, value)))
+    }
+
+    // Quick text dump of a sparse grid, the days draw theirs with Renderer
+    #[allow(dead_code)]
+    fn render<F>(&self, to_char: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut output = String::new();
+        let bounds = match self.bounds() {
+            None => return output,
+            Some(bounds) => bounds
+        };
+        for y in bounds.min.y..=bounds.max.y {
+            for x in bounds.min.x..=bounds.max.x {
+                output.push(self.get(&Position::new(x, y)).map_or(' ', &to_char));
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+impl<T> Grid<T> for Vec<Vec<T>> {
+    fn get(&self, position: &Position<i64>) -> Option<&T> {
+        if position.x < 0 || position.y < 0 {
+            return None;
+        }
+        self.as_slice().get(position.y as usize)?.get(position.x as usize)
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        let width = self.first()?.len() as i64;
+        if width == 0 {
+            return None;
+        }
+        Some(Bounds { min: Position::new(0, 0), max: Position::new(width - 1, self.len() as i64 - 1) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_are_inclusive() {
+        let bounds = Bounds { min: Position::new(-1, 2), max: Position::new(3, 2) };
+        assert_eq!((bounds.width(), bounds.height()), (5, 1));
+        assert!(bounds.contains(&Position::new(3, 2)));
+        assert!(!bounds.contains(&Position::new(3, 3)));
+    }
+
+    #[test]
+    fn dense_grids_stop_at_their_edges() {
+        let grid: Vec<Vec<u32>> = vec![vec![1, 2], vec![3, 4]];
+        assert_eq!(grid.get(&Position::new(-1, 0)), None);
+        let neighbours: Vec<(Position<i64>, &u32)> = grid.neighbours(&Position::new(0, 0), &Neighbourhood::VonNeumann).collect();
+        assert_eq!(neighbours, vec![(Position::new(0, 1), &3), (Position::new(1, 0), &2)]);
+        let diagonal: Vec<&u32> = grid.neighbours(&Position::new(0, 0), &Neighbourhood::Moore).map(|(_, value)| value).collect();
+        assert_eq!(diagonal, vec![&3, &2, &4]);
+        assert_eq!(grid.render(|value| char::from_digit(*value, 10).unwrap()), "12\n34\n");
+    }
+}
@@ -0,0 +1,95 @@
+use std::ops::{Add, Sub};
+
+/*
+    Hex grid coordinate in axial form (q, r).
+    The cube form adds s = -q - r so that the three axes always sum to zero,
+    which makes distances and neighbours the same on every axis.
+ */
+// No day is played on a hex grid yet
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct Hex {
+    pub(crate) q: i32,
+    pub(crate) r: i32,
+}
+
+#[allow(dead_code)]
+impl Hex {
+    // Starting east and going counter-clockwise
+    pub(crate) const DIRECTIONS: [Hex; 6] = [
+        Hex { q: 1, r: 0 },
+        Hex { q: 1, r: -1 },
+        Hex { q: 0, r: -1 },
+        Hex { q: -1, r: 0 },
+        Hex { q: -1, r: 1 },
+        Hex { q: 0, r: 1 },
+    ];
+
+    pub fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    // None when the cube coordinate isn't on the q + r + s = 0 plane
+    pub fn from_cube(q: i32, r: i32, s: i32) -> Option<Self> {
+        if q + r + s != 0 {
+            return None;
+        }
+        Some(Self { q, r })
+    }
+
+    pub fn s(&self) -> i32 {
+        -self.q - self.r
+    }
+
+    pub fn to_cube(self) -> (i32, i32, i32) {
+        (self.q, self.r, self.s())
+    }
+
+    pub fn neighbours(&self) -> [Hex; 6] {
+        Hex::DIRECTIONS.map(|direction| *self + direction)
+    }
+
+    // Number of steps between the two hexes
+    pub fn distance(&self, other: &Hex) -> i32 {
+        let delta = *self - *other;
+        (delta.q.abs() + delta.r.abs() + delta.s().abs()) / 2
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Hex::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Hex::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_coordinates_sum_to_zero() {
+        assert_eq!(Hex::from_cube(1, -3, 2), Some(Hex::new(1, -3)));
+        assert_eq!(Hex::from_cube(1, 1, 1), None);
+        assert_eq!(Hex::new(2, -5).to_cube(), (2, -5, 3));
+    }
+
+    #[test]
+    fn neighbours_are_one_step_away() {
+        let centre = Hex::new(3, -1);
+        for neighbour in centre.neighbours() {
+            assert_eq!(centre.distance(&neighbour), 1);
+        }
+        assert_eq!(Hex::new(0, 0).distance(&Hex::new(3, -1)), 3);
+        assert_eq!(Hex::new(-2, 0).distance(&Hex::new(2, -3)), 4);
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::hash::Hash;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::utils::grid::Grid;
+use crate::utils::position::Position;
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+// Cell value to colour lookup, anything not listed gets the default
+#[derive(Debug, Clone)]
+pub struct Palette<T> {
+    colours: HashMap<T, Rgb>,
+    default: Rgb,
+}
+
+impl<T: Eq + Hash> Palette<T> {
+    pub fn new(default: Rgb) -> Self {
+        Self { colours: HashMap::new(), default }
+    }
+
+    pub fn with(mut self, value: T, colour: Rgb) -> Self {
+        self.colours.insert(value, colour);
+        self
+    }
+
+    pub fn colour_of(&self, value: &T) -> Rgb {
+        *self.colours.get(value).unwrap_or(&self.default)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Self { width, height, pixels: vec![background; width * height] }
+    }
+
+    // Every cell becomes a scale x scale square, cells outside a dense grid's bounds are black
+    pub fn from_grid<T, G>(grid: &G, palette: &Palette<T>, scale: usize) -> Self
+    where
+        T: Eq + Hash,
+        G: Grid<T>,
+    {
+        let bounds = match grid.bounds() {
+            None => return Self::new(0, 0, BLACK),
+            Some(bounds) => bounds
+        };
+        let mut image = Self::new(bounds.width() as usize * scale, bounds.height() as usize * scale, BLACK);
+        for y in 0..bounds.height() {
+            for x in 0..bounds.width() {
+                let position = Position::new(bounds.min.x + x, bounds.min.y + y);
+                let colour = grid.get(&position).map_or(BLACK, |value| palette.colour_of(value));
+                image.fill_cell(x as usize, y as usize, scale, colour);
+            }
+        }
+        image
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = colour;
+        }
+    }
+
+    // Paints one scaled grid cell, handy for drawing a path on top of from_grid
+    pub fn fill_cell(&mut self, x: usize, y: usize, scale: usize, colour: Rgb) {
+        for dy in 0..scale {
+            for dx in 0..scale {
+                self.set(x * scale + dx, y * scale + dy, colour);
+            }
+        }
+    }
+
+    pub fn write_ppm(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for pixel in &self.pixels {
+            writer.write_all(pixel)?;
+        }
+        writer.flush()
+    }
+
+    /*
+        8-bit RGB PNG without compression: the zlib stream is made of stored deflate blocks,
+        so the only things to compute are the CRCs and the Adler-32 checksum.
+     */
+    pub fn write_png(&self, path: &Path) -> io::Result<()> {
+        let mut raw: Vec<u8> = Vec::with_capacity(self.height * (self.width * 3 + 1));
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0); // filter type: none
+            for pixel in row {
+                raw.extend_from_slice(pixel);
+            }
+        }
+
+        let mut header: Vec<u8> = Vec::new();
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        header.extend_from_slice(&[8, 2, 0, 0, 0]); // bit depth, truecolour, deflate, no filter, no interlace
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+        write_chunk(&mut writer, b"IHDR", &header)?;
+        write_chunk(&mut writer, b"IDAT", &zlib_stored(&raw))?;
+        write_chunk(&mut writer, b"IEND", &[])?;
+        writer.flush()
+    }
+}
+
+// The days write PNG, PPM is the format that any image tool can read without decoding
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+
+/*
+    Writes one numbered file per simulation step into a directory,
+    e.g. frames/guard_00000.png, frames/guard_00001.png, ...
+ */
+pub struct FrameSequence {
+    directory: PathBuf,
+    prefix: String,
+    format: ImageFormat,
+    next_frame: usize,
+}
+
+impl FrameSequence {
+    pub fn new(directory: &Path, prefix: &str, format: ImageFormat) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+        Ok(Self { directory: directory.to_path_buf(), prefix: prefix.to_string(), format, next_frame: 0 })
+    }
+
+    pub fn write_frame(&mut self, image: &Image) -> io::Result<PathBuf> {
+        let extension = match self.format {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+        };
+        let path = self.directory.join(format!("{}_{:05}.{}", self.prefix, self.next_frame, extension));
+        match self.format {
+            ImageFormat::Ppm => image.write_ppm(&path)?,
+            ImageFormat::Png => image.write_png(&path)?,
+        }
+        self.next_frame += 1;
+        Ok(path)
+    }
+
+    pub fn frames_written(&self) -> usize {
+        self.next_frame
+    }
+}
+
+fn write_chunk(writer: &mut impl Write, chunk_type: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(chunk_type)?;
+    writer.write_all(data)?;
+    let mut crc = Crc32::new();
+    crc.update(chunk_type);
+    crc.update(data);
+    writer.write_all(&crc.finish().to_be_bytes())
+}
+
+// Stored blocks hold at most 65535 bytes each
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65535;
+    let mut output: Vec<u8> = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        output.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let length = block.len() as u16;
+        output.push(is_final as u8);
+        output.extend_from_slice(&length.to_le_bytes());
+        output.extend_from_slice(&(!length).to_le_bytes());
+        output.extend_from_slice(block);
+    }
+    output.extend_from_slice(&adler32(data).to_be_bytes());
+    output
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % MOD_ADLER;
+        b = (b + a) % MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+struct Crc32 {
+    table: [u32; 256],
+    value: u32,
+}
+
+impl Crc32 {
+    fn new() -> Self {
+        let mut table = [0u32; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+            }
+            *entry = c;
+        }
+        Self { table, value: 0xffff_ffff }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.value = self.table[((self.value ^ *byte as u32) & 0xff) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.value ^ 0xffff_ffff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn scratch_directory(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("aoc-image-{}-{}", name, process::id()))
+    }
+
+    // A black and a white cell side by side, each drawn 2 pixels wide
+    fn sample_image() -> Image {
+        let grid = vec![vec![0, 1]];
+        let palette = Palette::new(BLACK).with(1, WHITE);
+        Image::from_grid(&grid, &palette, 2)
+    }
+
+    #[test]
+    fn from_grid_scales_every_cell() {
+        let image = sample_image();
+        assert_eq!((image.width, image.height), (4, 2));
+        let row = [BLACK, BLACK, WHITE, WHITE];
+        assert_eq!(image.pixels, [row, row].concat());
+    }
+
+    #[test]
+    fn writes_ppm_and_png() {
+        let directory = scratch_directory("formats");
+        fs::create_dir_all(&directory).unwrap();
+        let image = sample_image();
+
+        let ppm_path = directory.join("sample.ppm");
+        image.write_ppm(&ppm_path).unwrap();
+        let ppm = fs::read(&ppm_path).unwrap();
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 4 * 2 * 3);
+
+        let png_path = directory.join("sample.png");
+        image.write_png(&png_path).unwrap();
+        let png = fs::read(&png_path).unwrap();
+        assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+        assert_eq!(&png[12..16], b"IHDR");
+        // An empty IEND chunk always ends in the same CRC
+        assert_eq!(&png[png.len() - 12..], &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        assert_eq!(crc.finish(), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn frame_sequence_numbers_its_files() {
+        let directory = scratch_directory("frames");
+        let mut frames = FrameSequence::new(&directory, "guard", ImageFormat::Png).unwrap();
+        let image = sample_image();
+        assert_eq!(frames.write_frame(&image).unwrap(), directory.join("guard_00000.png"));
+        assert_eq!(frames.write_frame(&image).unwrap(), directory.join("guard_00001.png"));
+        assert_eq!(frames.frames_written(), 2);
+
+        let mut ppm_frames = FrameSequence::new(&directory, "ppm", ImageFormat::Ppm).unwrap();
+        assert!(ppm_frames.write_frame(&image).unwrap().exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/*
+    Set of integers stored as disjoint half-open ranges (start..end).
+    Overlapping and touching ranges are merged as they are inserted,
+    so 0..3 and 3..5 are kept as the single span 0..5.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeSet {
+    spans: BTreeMap<i64, i64>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        // A span starting before us can only touch us if it ends at or after our start
+        if let Some((&previous_start, &previous_end)) = self.spans.range(..=start).next_back() {
+            if previous_end >= start {
+                start = previous_start;
+                end = end.max(previous_end);
+            }
+        }
+        let swallowed: Vec<(i64, i64)> = self.spans.range(start..=end).map(|(&s, &e)| (s, e)).collect();
+        for (span_start, span_end) in swallowed {
+            self.spans.remove(&span_start);
+            end = end.max(span_end);
+        }
+        self.spans.insert(start, end);
+    }
+
+    pub fn remove(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        let overlapping: Vec<(i64, i64)> = self.spans.range(..range.end)
+            .filter(|(_, &end)| end > range.start)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (span_start, span_end) in overlapping {
+            self.spans.remove(&span_start);
+            if span_start < range.start {
+                self.spans.insert(span_start, range.start);
+            }
+            if span_end > range.end {
+                self.spans.insert(range.end, span_end);
+            }
+        }
+    }
+
+    /*
+        Start of the first free run of at least min_length values at or after from.
+        Everything past the last span is free, so there is always an answer.
+     */
+    pub fn first_gap(&self, from: i64, min_length: i64) -> i64 {
+        let mut candidate = from;
+        if let Some((_, &end)) = self.spans.range(..=from).next_back() {
+            candidate = candidate.max(end);
+        }
+        for (&start, &end) in self.spans.range(candidate..) {
+            if start - candidate >= min_length {
+                break;
+            }
+            candidate = end;
+        }
+        candidate
+    }
+}
+
+// Queries day 9 doesn't need, it only ever asks for the first gap a file fits in
+#[allow(dead_code)]
+impl RangeSet {
+    pub fn contains(&self, value: i64) -> bool {
+        self.spans.range(..=value).next_back().is_some_and(|(_, &end)| value < end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    // How many integers are in the set, not how many spans
+    pub fn covered(&self) -> i64 {
+        self.spans.iter().map(|(start, end)| end - start).sum()
+    }
+
+    // Occupied spans in increasing order
+    pub fn spans(&self) -> impl Iterator<Item = Range<i64>> + '_ {
+        self.spans.iter().map(|(&start, &end)| start..end)
+    }
+
+    // Free spans between the first and the last occupied span, in increasing order
+    pub fn gaps(&self) -> impl Iterator<Item = Range<i64>> + '_ {
+        self.spans.iter()
+            .zip(self.spans.iter().skip(1))
+            .map(|((_, &previous_end), (&next_start, _))| previous_end..next_start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touching_ranges_merge() {
+        let mut set = RangeSet::new();
+        assert!(set.is_empty());
+        set.insert(0..3);
+        set.insert(3..5);
+        set.insert(8..10);
+        assert_eq!(set.spans().collect::<Vec<_>>(), vec![0..5, 8..10]);
+        assert_eq!(set.gaps().collect::<Vec<_>>(), vec![5..8]);
+        assert_eq!(set.covered(), 7);
+        assert!(set.contains(4));
+        assert!(!set.contains(5));
+    }
+
+    #[test]
+    fn remove_splits_spans() {
+        let mut set = RangeSet::new();
+        set.insert(0..10);
+        set.remove(3..5);
+        assert_eq!(set.spans().collect::<Vec<_>>(), vec![0..3, 5..10]);
+        assert_eq!(set.first_gap(0, 2), 3);
+        assert_eq!(set.first_gap(0, 3), 10);
+    }
+}
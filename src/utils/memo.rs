@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub(crate) hits: usize,
+    pub(crate) misses: usize,
+    pub(crate) entries: usize,
+}
+
+/*
+    Cache for recursive solvers keyed by the arguments that change between calls.
+    The recursive function takes the memo as its first argument and wraps its body in
+    get_or_compute, recursing through the &mut Memo it is handed:
+
+        fn count(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+            memo.get_or_compute(n, |memo| count(memo, n - 1) + ...)
+        }
+
+    A Memo is plain data, so keeping one in a thread_local (or passing it along) lets
+    part 2 reuse everything part 1 already worked out.
+ */
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    stats: CacheStats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self { cache: HashMap::new(), stats: CacheStats::default() }
+    }
+}
+
+impl<K, V> Memo<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_or_compute<F>(&mut self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    // Peeking without computing, no solver needs it yet
+    #[allow(dead_code)]
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats { entries: self.cache.len(), ..self.stats }
+    }
+
+    // Drops the cached values and resets the counters
+    // The thread_local memos live for the whole run
+    #[allow(dead_code)]
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.stats = CacheStats::default();
+    }
+
+    // Keeps the cached values but starts counting hits and misses from zero,
+    // handy for seeing how much part 2 gained from part 1's entries
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_compute(n, |memo| fibonacci(memo, n - 1) + fibonacci(memo, n - 2))
+    }
+
+    #[test]
+    fn computes_every_key_once() {
+        let mut memo = Memo::new();
+        assert_eq!(fibonacci(&mut memo, 50), 12_586_269_025);
+        assert_eq!(memo.stats().misses, 49);
+        assert_eq!(memo.get(&10), Some(&55));
+        assert_eq!(memo.get(&51), None);
+    }
+
+    #[test]
+    fn clear_forgets_values_and_counts() {
+        let mut memo = Memo::new();
+        fibonacci(&mut memo, 10);
+        memo.reset_stats();
+        fibonacci(&mut memo, 10);
+        assert_eq!(memo.stats(), CacheStats { hits: 1, misses: 0, entries: 9 });
+        memo.clear();
+        assert_eq!(memo.stats(), CacheStats::default());
+        assert_eq!(memo.get(&10), None);
+    }
+}
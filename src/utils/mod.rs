@@ -1,2 +1,22 @@
+pub mod asciicast;
+pub mod assignment;
+pub mod checked;
+pub mod counter;
+pub mod csv;
+pub mod cycle;
+pub mod digits;
 pub mod direction;
+pub mod external_sort;
+pub mod grid;
+pub mod hex;
+pub mod image;
+pub mod intervals;
+pub mod memo;
+pub mod neighbourhood;
+pub mod numtheory;
+pub mod parse;
 pub mod position;
+pub mod regions;
+pub mod render;
+pub mod sparse_grid;
+pub mod word_search;
@@ -0,0 +1,66 @@
+use crate::utils::direction::Direction;
+use crate::utils::position::Position;
+
+/*
+    Which cells count as next to each other.
+    VonNeumann is the four orthogonal steps, Moore adds the diagonals,
+    Knight is the chess knight's L shaped jumps and Custom takes any offsets.
+ */
+// Only VonNeumann is walked by a day so far
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+    VonNeumann,
+    Moore,
+    Knight,
+    Custom(Vec<Direction>),
+}
+
+impl Neighbourhood {
+    pub fn offsets(&self) -> &[Direction] {
+        match self {
+            Neighbourhood::VonNeumann => &Direction::CARDINAL_DIRECTIONS,
+            Neighbourhood::Moore => &Direction::ALL_DIRECTIONS,
+            Neighbourhood::Knight => &Direction::KNIGHT_MOVES,
+            Neighbourhood::Custom(offsets) => offsets,
+        }
+    }
+
+    // Unchecked, callers still have to do their own bounds checks
+    pub fn neighbours(&self, x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.offsets().iter().map(move |direction| direction.apply_movement(x, y))
+    }
+
+    pub fn neighbour_positions(&self, position: &Position<i64>) -> impl Iterator<Item = Position<i64>> + '_ {
+        let position = *position;
+        self.offsets().iter().map(move |direction| direction.step(&position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moore_adds_the_diagonals() {
+        let mut neighbours: Vec<(i32, i32)> = Neighbourhood::Moore.neighbours(0, 0).collect();
+        neighbours.sort_unstable();
+        assert_eq!(neighbours, vec![(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn knight_jumps_in_an_l() {
+        let neighbours: Vec<Position<i64>> = Neighbourhood::Knight.neighbour_positions(&Position::new(4, 4)).collect();
+        assert_eq!(neighbours.len(), 8);
+        for position in neighbours {
+            let (dx, dy) = ((position.x - 4).abs(), (position.y - 4).abs());
+            assert!((dx, dy) == (1, 2) || (dx, dy) == (2, 1));
+        }
+    }
+
+    #[test]
+    fn custom_takes_any_offsets() {
+        let neighbourhood = Neighbourhood::Custom(vec![Direction::EAST, Direction::new(3, 3)]);
+        assert_eq!(neighbourhood.neighbours(1, 1).collect::<Vec<_>>(), vec![(2, 1), (4, 4)]);
+    }
+}
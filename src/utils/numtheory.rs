@@ -0,0 +1,234 @@
+use num_traits::{Euclid, Signed};
+
+// Anything that behaves like a signed integer: i64, i128 and BigInt all qualify
+pub trait Integer: Clone + Ord + Signed + Euclid {}
+
+impl<T: Clone + Ord + Signed + Euclid> Integer for T {}
+
+// Always non-negative, gcd(0, 0) is 0
+pub fn gcd<T: Integer>(a: &T, b: &T) -> T {
+    let mut a = a.abs();
+    let mut b = b.abs();
+    while !b.is_zero() {
+        let remainder = a.rem_euclid(&b);
+        a = b;
+        b = remainder;
+    }
+    a
+}
+
+// Day 13 only needs the extended form
+#[allow(dead_code)]
+pub fn lcm<T: Integer>(a: &T, b: &T) -> T {
+    if a.is_zero() || b.is_zero() {
+        return T::zero();
+    }
+    (a.clone() / gcd(a, b) * b.clone()).abs()
+}
+
+// Returns (g, x, y) such that a*x + b*y = g = gcd(a, b)
+pub fn extended_gcd<T: Integer>(a: &T, b: &T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a.clone(), b.clone());
+    let (mut old_s, mut s) = (T::one(), T::zero());
+    let (mut old_t, mut t) = (T::zero(), T::one());
+    while !r.is_zero() {
+        let quotient = old_r.clone() / r.clone();
+        (old_r, r) = (r.clone(), old_r - quotient.clone() * r);
+        (old_s, s) = (s.clone(), old_s - quotient.clone() * s);
+        (old_t, t) = (t.clone(), old_t - quotient * t);
+    }
+    if old_r.is_negative() {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+// x such that a*x = 1 (mod m), in the range [0, m)
+// Kept with crt for puzzles with cycles that line up, none this year so far
+#[allow(dead_code)]
+pub fn mod_inverse<T: Integer>(a: &T, modulus: &T) -> Option<T> {
+    let modulus = modulus.abs();
+    let (g, x, _) = extended_gcd(&a.rem_euclid(&modulus), &modulus);
+    if !g.is_one() {
+        return None;
+    }
+    Some(x.rem_euclid(&modulus))
+}
+
+/*
+    Chinese Remainder Theorem over (residue, modulus) pairs.
+    The moduli don't need to be coprime, so this returns None when the congruences contradict
+    each other, otherwise (x, l) where x is the smallest non-negative solution and l is the lcm
+    of the moduli (every solution is x + k*l).
+ */
+// Unused like mod_inverse
+#[allow(dead_code)]
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut result = T::zero();
+    let mut combined_modulus = T::one();
+    for (residue, modulus) in congruences {
+        let modulus = modulus.abs();
+        if modulus.is_zero() {
+            return None;
+        }
+        let (g, p, _) = extended_gcd(&combined_modulus, &modulus);
+        let difference = residue.clone() - result.clone();
+        if !difference.rem_euclid(&g).is_zero() {
+            return None;
+        }
+        let step_modulus = modulus.clone() / g.clone();
+        let k = (difference / g * p).rem_euclid(&step_modulus);
+        result = result + combined_modulus.clone() * k;
+        combined_modulus = combined_modulus * step_modulus;
+        result = result.rem_euclid(&combined_modulus);
+    }
+    Some((result, combined_modulus))
+}
+
+/*
+    Every integer solution of a*x + b*y = c, written as
+        x = x0 + k * step_x
+        y = y0 + k * step_y
+    for any integer k.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diophantine<T> {
+    pub(crate) x0: T,
+    pub(crate) y0: T,
+    pub(crate) step_x: T,
+    pub(crate) step_y: T,
+}
+
+// None when there is no integer solution, or when a and b are both zero (no single line to describe)
+pub fn solve_diophantine<T: Integer>(a: &T, b: &T, c: &T) -> Option<Diophantine<T>> {
+    if a.is_zero() && b.is_zero() {
+        return None;
+    }
+    let (g, x, y) = extended_gcd(a, b);
+    if !c.rem_euclid(&g).is_zero() {
+        return None;
+    }
+    let scale = c.clone() / g.clone();
+    Some(Diophantine {
+        x0: x * scale.clone(),
+        y0: y * scale,
+        step_x: b.clone() / g.clone(),
+        step_y: -(a.clone() / g),
+    })
+}
+
+impl<T: Integer> Diophantine<T> {
+    pub fn at(&self, k: &T) -> (T, T) {
+        (
+            self.x0.clone() + k.clone() * self.step_x.clone(),
+            self.y0.clone() + k.clone() * self.step_y.clone(),
+        )
+    }
+
+    /*
+        Inclusive range of k for which both x and y are non-negative, or None if there are no such k.
+        A None bound means the range is open on that side.
+     */
+    pub fn non_negative_range(&self) -> Option<(Option<T>, Option<T>)> {
+        let mut lower: Option<T> = None;
+        let mut upper: Option<T> = None;
+        for (start, step) in [(&self.x0, &self.step_x), (&self.y0, &self.step_y)] {
+            // start + k * step >= 0
+            if step.is_zero() {
+                if start.is_negative() {
+                    return None;
+                }
+            } else if step.is_positive() {
+                let bound = ceil_div(&-start.clone(), step);
+                lower = Some(lower.map_or(bound.clone(), |current| current.max(bound)));
+            } else {
+                let bound = floor_div(start, &-step.clone());
+                upper = Some(upper.map_or(bound.clone(), |current| current.min(bound)));
+            }
+        }
+        if let (Some(lower), Some(upper)) = (&lower, &upper) {
+            if lower > upper {
+                return None;
+            }
+        }
+        Some((lower, upper))
+    }
+
+    /*
+        Walks the non-negative solutions starting from the closed end of the range,
+        endless if the other end is open. At least one end is always closed because
+        step_x and step_y can't both be zero.
+     */
+    // Day 13 picks the cheapest solution from the range directly
+    #[allow(dead_code)]
+    pub fn non_negative_solutions(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        let (mut k, end, step) = match self.non_negative_range() {
+            None => (T::zero(), Some(-T::one()), T::one()),
+            Some((Some(lower), upper)) => (lower, upper, T::one()),
+            Some((None, Some(upper))) => (upper, None, -T::one()),
+            Some((None, None)) => unreachable!("step_x and step_y are never both zero"),
+        };
+        std::iter::from_fn(move || {
+            if let Some(end) = &end {
+                if (step.is_positive() && &k > end) || (step.is_negative() && &k < end) {
+                    return None;
+                }
+            }
+            let solution = self.at(&k);
+            k = k.clone() + step.clone();
+            Some(solution)
+        })
+    }
+}
+
+fn floor_div<T: Integer>(a: &T, b: &T) -> T {
+    // b is always positive here, where div_euclid rounds down
+    a.div_euclid(b)
+}
+
+fn ceil_div<T: Integer>(a: &T, b: &T) -> T {
+    -floor_div(&-a.clone(), b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+
+    #[test]
+    fn gcd_and_lcm_ignore_signs() {
+        assert_eq!(gcd(&-12i64, &18), 6);
+        assert_eq!(gcd(&0i64, &0), 0);
+        assert_eq!(lcm(&-4i64, &6), 12);
+        assert_eq!(lcm(&0i64, &6), 0);
+    }
+
+    #[test]
+    fn mod_inverse_needs_coprime_values() {
+        assert_eq!(mod_inverse(&3i64, &11), Some(4));
+        assert_eq!(mod_inverse(&-3i64, &11), Some(7));
+        assert_eq!(mod_inverse(&2i64, &4), None);
+        assert_eq!(mod_inverse(&BigInt::from(10), &BigInt::from(17)), Some(BigInt::from(12)));
+    }
+
+    #[test]
+    fn crt_handles_moduli_that_share_factors() {
+        assert_eq!(crt(&[(2i64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1i64, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1i64, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(1i64, 0)]), None);
+    }
+
+    #[test]
+    fn non_negative_solutions_are_all_found() {
+        let line = solve_diophantine(&2i64, &3, &12).unwrap();
+        let mut solutions: Vec<(i64, i64)> = line.non_negative_solutions().collect();
+        solutions.sort_unstable();
+        assert_eq!(solutions, vec![(0, 4), (3, 2), (6, 0)]);
+
+        let none = solve_diophantine(&2i64, &3, &1).unwrap();
+        assert_eq!(none.non_negative_solutions().count(), 0);
+        assert_eq!(solve_diophantine(&2i64, &4, &3), None);
+    }
+}
@@ -0,0 +1,186 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Line numbers are 1-based and relative to the text that was handed to the parser
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidNumber { line: usize, token: String },
+    FieldCount { line: usize, expected: usize, found: usize },
+    RaggedGrid { line: usize, expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidNumber { line, token } => {
+                write!(f, "line {}: could not parse '{}' as a number", line, token)
+            }
+            ParseError::FieldCount { line, expected, found } => {
+                write!(f, "line {}: expected {} numbers but found {}", line, expected, found)
+            }
+            ParseError::RaggedGrid { line, expected, found } => {
+                write!(f, "line {}: expected a row of width {} but found {}", line, expected, found)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl ParseError {
+    // For callers that parse one line at a time and know where that line sits in the file
+    pub fn at_line(self, line: usize) -> ParseError {
+        match self {
+            ParseError::InvalidNumber { token, .. } => ParseError::InvalidNumber { line, token },
+            ParseError::FieldCount { expected, found, .. } => ParseError::FieldCount { line, expected, found },
+            ParseError::RaggedGrid { expected, found, .. } => ParseError::RaggedGrid { line, expected, found },
+        }
+    }
+}
+
+/*
+    Pulls every integer out of the text and ignores everything around it,
+    so "Button A: X+94, Y+34" gives [94, 34] and "X=-5" gives [-5].
+    A '-' is only treated as a sign when it is not directly after a digit,
+    which keeps ranges like "1-3" as [1, 3].
+ */
+pub fn integers<T: FromStr>(text: &str) -> Result<Vec<T>, ParseError> {
+    let mut result: Vec<T> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let bytes = line.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            let is_sign = bytes[i] == b'-'
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+                && (i == 0 || !bytes[i - 1].is_ascii_digit());
+            if !is_sign && !bytes[i].is_ascii_digit() {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            let token = &line[start..i];
+            match token.parse() {
+                Ok(value) => result.push(value),
+                Err(_) => return Err(ParseError::InvalidNumber { line: index + 1, token: token.to_string() }),
+            }
+        }
+    }
+    Ok(result)
+}
+
+// Exactly N integers from the text, e.g. one "a|b" rule or one whole claw machine section
+pub fn record<T: FromStr, const N: usize>(text: &str) -> Result<[T; N], ParseError> {
+    let values: Vec<T> = integers(text)?;
+    let found = values.len();
+    values.try_into().map_err(|_| ParseError::FieldCount { line: 1, expected: N, found })
+}
+
+// One record of N integers per non-blank line
+pub fn records<T: FromStr, const N: usize>(text: &str) -> Result<Vec<[T; N]>, ParseError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| record(line).map_err(|error| error.at_line(index + 1)))
+        .collect()
+}
+
+// Blank-line separated blocks, trailing newlines and whitespace-only lines count as blank
+pub fn sections(text: &str) -> Vec<&str> {
+    let mut result: Vec<&str> = Vec::new();
+    let mut section_start: Option<usize> = None;
+    let mut section_end = 0;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(start) = section_start.take() {
+                result.push(&text[start..section_end]);
+            }
+        } else {
+            section_start.get_or_insert(offset);
+            section_end = offset + line.trim_end_matches(['\r', '\n']).len();
+        }
+        offset += line.len();
+    }
+    if let Some(start) = section_start {
+        result.push(&text[start..section_end]);
+    }
+    result
+}
+
+/*
+    Rectangular character grid indexed as grid[y][x].
+    Blank lines before and after the grid are ignored, a blank line inside it
+    is a row of width 0 and so a RaggedGrid error like any other short row.
+ */
+pub fn char_grid(text: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let first = match lines.iter().position(|line| !line.is_empty()) {
+        None => return Ok(Vec::new()),
+        Some(first) => first,
+    };
+    let last = lines.iter().rposition(|line| !line.is_empty()).unwrap_or(first);
+
+    let mut grid: Vec<Vec<char>> = Vec::new();
+    for (index, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let row: Vec<char> = line.chars().collect();
+        if let Some(first_row) = grid.first() {
+            if first_row.len() != row.len() {
+                return Err(ParseError::RaggedGrid { line: index + 1, expected: first_row.len(), found: row.len() });
+            }
+        }
+        grid.push(row);
+    }
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minus_is_a_sign_unless_it_follows_a_digit() {
+        assert_eq!(integers::<i32>("X=-5"), Ok(vec![-5]));
+        assert_eq!(integers::<i32>("1-3"), Ok(vec![1, 3]));
+        assert_eq!(integers::<i32>("Button A: X+94, Y+34"), Ok(vec![94, 34]));
+        assert_eq!(integers::<i32>("a - b --7"), Ok(vec![-7]));
+    }
+
+    #[test]
+    fn numbers_that_dont_fit_are_reported() {
+        assert_eq!(
+            integers::<u8>("1\n2 300"),
+            Err(ParseError::InvalidNumber { line: 2, token: "300".to_string() })
+        );
+        assert!(integers::<u32>("-4").is_err());
+    }
+
+    #[test]
+    fn sections_handle_crlf_and_trailing_blank_lines() {
+        let text = "a: 1\r\nb: 2\r\n\r\nc: 3\r\n  \r\n\r\n";
+        assert_eq!(sections(text), vec!["a: 1\r\nb: 2", "c: 3"]);
+        assert_eq!(sections("one\n\n\ntwo"), vec!["one", "two"]);
+        assert!(sections("\n \n").is_empty());
+    }
+
+    #[test]
+    fn records_report_the_line_of_a_short_record() {
+        assert_eq!(records::<i32, 2>("47|53\n\n97|13\n"), Ok(vec![[47, 53], [97, 13]]));
+        let error = records::<i32, 2>("47|53\n\n97\n").unwrap_err();
+        assert_eq!(error, ParseError::FieldCount { line: 3, expected: 2, found: 1 });
+        assert_eq!(error.to_string(), "line 3: expected 2 numbers but found 1");
+    }
+
+    #[test]
+    fn char_grid_rejects_ragged_rows() {
+        assert_eq!(char_grid("ab\ncd\n\n"), Ok(vec![vec!['a', 'b'], vec!['c', 'd']]));
+        assert_eq!(char_grid("ab\r\ncd\r\n"), Ok(vec![vec!['a', 'b'], vec!['c', 'd']]));
+        assert_eq!(char_grid("ab\nc\n"), Err(ParseError::RaggedGrid { line: 2, expected: 2, found: 1 }));
+        assert_eq!(char_grid("ab\n\ncd"), Err(ParseError::RaggedGrid { line: 2, expected: 2, found: 0 }));
+        assert_eq!(char_grid(""), Ok(Vec::new()));
+    }
+}
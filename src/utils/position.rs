@@ -1,4 +1,4 @@
-use std::ops::{Add, Sub};
+use std::ops::{Add, Neg, Sub};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub struct Position<T> {
@@ -27,4 +27,218 @@ where
             y: self.y - other.y,
         }
     }
-}
\ No newline at end of file
+}
+
+// Every 2024 puzzle so far is flat, this is for the 3D ones
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct Position3<T> {
+    pub(crate) x: T,
+    pub(crate) y: T,
+    pub(crate) z: T,
+}
+
+#[allow(dead_code)]
+impl<T> Position3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+
+    pub fn subtract(&self, other: &Self) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T> Position3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + PartialOrd,
+{
+    pub fn manhattan_distance(&self, other: &Self) -> T {
+        let distance = |a: T, b: T| if a > b { a - b } else { b - a };
+        distance(self.x, other.x) + distance(self.y, other.y) + distance(self.z, other.z)
+    }
+}
+
+#[allow(dead_code)]
+impl<T> Position3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + From<i8>,
+{
+    // Face neighbours
+    pub fn neighbours_6(&self) -> Vec<Self> {
+        let (zero, one) = (T::from(0), T::from(1));
+        [
+            (one, zero, zero),
+            (zero, one, zero),
+            (zero, zero, one),
+        ].iter().flat_map(|&(x, y, z)| {
+            let offset = Self::new(x, y, z);
+            [self.add(&offset), self.subtract(&offset)]
+        }).collect()
+    }
+
+    // Face, edge and corner neighbours
+    pub fn neighbours_26(&self) -> Vec<Self> {
+        let mut neighbours = Vec::with_capacity(26);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    if dx == 0 && dy == 0 && dz == 0 {
+                        continue;
+                    }
+                    neighbours.push(self.add(&Self::new(T::from(dx), T::from(dy), T::from(dz))));
+                }
+            }
+        }
+        neighbours
+    }
+}
+
+/*
+    Quarter turns around the origin, right handed: looking down the axis towards the
+    origin, the other two axes turn counter-clockwise.
+ */
+#[allow(dead_code)]
+impl<T> Position3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Neg<Output = T>,
+{
+    pub fn rotate_x(&self) -> Self {
+        Self::new(self.x, -self.z, self.y)
+    }
+
+    pub fn rotate_y(&self) -> Self {
+        Self::new(self.z, self.y, -self.x)
+    }
+
+    pub fn rotate_z(&self) -> Self {
+        Self::new(-self.y, self.x, self.z)
+    }
+
+    // All 24 orientations: point the x axis at each of the 6 faces, then spin around it 4 times
+    pub fn rotations(&self) -> Vec<Self> {
+        let facings = [
+            *self,
+            self.rotate_y(),
+            self.rotate_y().rotate_y(),
+            self.rotate_y().rotate_y().rotate_y(),
+            self.rotate_z(),
+            self.rotate_z().rotate_z().rotate_z(),
+        ];
+        let mut rotations = Vec::with_capacity(24);
+        for facing in facings {
+            let mut spun = facing;
+            for _ in 0..4 {
+                rotations.push(spun);
+                spun = spun.rotate_x();
+            }
+        }
+        rotations
+    }
+}
+
+// Axis aligned box, inclusive on both corners
+// Unused like Position3
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct Bounds3<T> {
+    pub(crate) min: Position3<T>,
+    pub(crate) max: Position3<T>,
+}
+
+#[allow(dead_code)]
+impl<T> Bounds3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + PartialOrd,
+{
+    // Smallest box holding every point, None when there are none
+    pub fn from_points<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Position3<T>>,
+        T: 'a,
+    {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = Self { min: *first, max: *first };
+        for point in points {
+            bounds.include(point);
+        }
+        Some(bounds)
+    }
+
+    // Grows the box to cover the point
+    pub fn include(&mut self, point: &Position3<T>) {
+        let lower = |a: T, b: T| if b < a { b } else { a };
+        let higher = |a: T, b: T| if b > a { b } else { a };
+        self.min = Position3::new(lower(self.min.x, point.x), lower(self.min.y, point.y), lower(self.min.z, point.z));
+        self.max = Position3::new(higher(self.max.x, point.x), higher(self.max.y, point.y), higher(self.max.z, point.z));
+    }
+
+    pub fn contains(&self, point: &Position3<T>) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x
+            && point.y >= self.min.y && point.y <= self.max.y
+            && point.z >= self.min.z && point.z <= self.max.z
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn position3_arithmetic() {
+        let a = Position3::new(1i64, -2, 3);
+        let b = Position3::new(4i64, 0, -1);
+        assert_eq!(a.add(&b), Position3::new(5, -2, 2));
+        assert_eq!(a.subtract(&b), Position3::new(-3, -2, 4));
+        assert_eq!(a.manhattan_distance(&b), 9);
+    }
+
+    #[test]
+    fn position3_neighbours() {
+        let origin = Position3::new(0i64, 0, 0);
+        let faces = origin.neighbours_6();
+        assert_eq!(faces.len(), 6);
+        assert!(faces.iter().all(|neighbour| origin.manhattan_distance(neighbour) == 1));
+        let all: HashSet<Position3<i64>> = origin.neighbours_26().into_iter().collect();
+        assert_eq!(all.len(), 26);
+        assert!(!all.contains(&origin));
+    }
+
+    #[test]
+    fn rotations_cover_all_24_orientations() {
+        let point = Position3::new(1i64, 2, 3);
+        assert_eq!(point.rotate_z(), Position3::new(-2, 1, 3));
+        let rotations: HashSet<Position3<i64>> = point.rotations().into_iter().collect();
+        assert_eq!(rotations.len(), 24);
+        assert!(rotations.contains(&point));
+    }
+
+    #[test]
+    fn bounds3_grow_to_fit() {
+        let points = [Position3::new(1i64, 5, -2), Position3::new(-3, 2, 0), Position3::new(0, 7, 4)];
+        let bounds = Bounds3::from_points(&points).unwrap();
+        assert_eq!(bounds.min, Position3::new(-3, 2, -2));
+        assert_eq!(bounds.max, Position3::new(1, 7, 4));
+        assert!(points.iter().all(|point| bounds.contains(point)));
+        assert!(!bounds.contains(&Position3::new(2, 5, 0)));
+        assert_eq!(Bounds3::<i64>::from_points(&[]), None);
+    }
+}
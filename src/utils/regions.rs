@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use crate::utils::neighbourhood::Neighbourhood;
+use crate::utils::position::Position;
+
+/*
+    Result of labelling a whole grid.
+    labels[y][x] is the index of the component that cell belongs to,
+    and components[label] holds every cell of that component.
+ */
+#[derive(Debug)]
+pub struct Regions {
+    pub(crate) labels: Vec<Vec<usize>>,
+    pub(crate) components: Vec<Vec<Position<usize>>>,
+}
+
+impl Regions {
+    pub fn label_at(&self, position: &Position<usize>) -> usize {
+        self.labels[position.y][position.x]
+    }
+
+    // Day 12 walks every component in turn rather than looking one up
+    #[allow(dead_code)]
+    pub fn component(&self, label: usize) -> &[Position<usize>] {
+        &self.components[label]
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    // Kept beside len, clippy expects the pair
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Vec<Position<usize>>> {
+        self.components.iter()
+    }
+}
+
+// Iterative so that large single-value regions can't blow the stack
+// For filling from a single cell, day 12 labels the whole grid
+#[allow(dead_code)]
+pub fn flood_fill<T, F>(grid: &[Vec<T>], start: Position<usize>, neighbourhood: &Neighbourhood, same_region: F) -> Vec<Position<usize>>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut visited: Vec<Vec<bool>> = grid.iter().map(|row| vec![false; row.len()]).collect();
+    fill_from(grid, start, neighbourhood, &same_region, &mut visited)
+}
+
+pub fn label_components<T, F>(grid: &[Vec<T>], neighbourhood: &Neighbourhood, same_region: F) -> Regions
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut visited: Vec<Vec<bool>> = grid.iter().map(|row| vec![false; row.len()]).collect();
+    let mut labels: Vec<Vec<usize>> = grid.iter().map(|row| vec![0; row.len()]).collect();
+    let mut components: Vec<Vec<Position<usize>>> = Vec::new();
+
+    for (y, row) in grid.iter().enumerate() {
+        for x in 0..row.len() {
+            if visited[y][x] {
+                continue;
+            }
+            let component = fill_from(grid, Position::new(x, y), neighbourhood, &same_region, &mut visited);
+            for cell in &component {
+                labels[cell.y][cell.x] = components.len();
+            }
+            components.push(component);
+        }
+    }
+    Regions { labels, components }
+}
+
+fn fill_from<T, F>(grid: &[Vec<T>], start: Position<usize>, neighbourhood: &Neighbourhood, same_region: &F, visited: &mut [Vec<bool>]) -> Vec<Position<usize>>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut cells: Vec<Position<usize>> = Vec::new();
+    let mut queue: VecDeque<Position<usize>> = VecDeque::new();
+    visited[start.y][start.x] = true;
+    queue.push_back(start);
+
+    while let Some(current) = queue.pop_front() {
+        cells.push(current);
+        let current_value = &grid[current.y][current.x];
+        for (next_x, next_y) in neighbourhood.neighbours(current.x as i32, current.y as i32) {
+            if next_y < 0 || next_y >= grid.len() as i32 || next_x < 0 || next_x >= grid[next_y as usize].len() as i32 {
+                continue;
+            }
+            let (next_x, next_y) = (next_x as usize, next_y as usize);
+            if visited[next_y][next_x] || !same_region(current_value, &grid[next_y][next_x]) {
+                continue;
+            }
+            visited[next_y][next_x] = true;
+            queue.push_back(Position::new(next_x, next_y));
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_every_cell() {
+        let grid: Vec<Vec<char>> = ["AAB", "ABB", "CCB"].iter().map(|row| row.chars().collect()).collect();
+        let regions = label_components(&grid, &Neighbourhood::VonNeumann, |a, b| a == b);
+        assert_eq!(regions.len(), 3);
+        assert!(!regions.is_empty());
+        let b = regions.label_at(&Position::new(2, 0));
+        assert_eq!(regions.component(b).len(), 4);
+        assert_eq!(regions.label_at(&Position::new(1, 1)), b);
+        assert_ne!(regions.label_at(&Position::new(0, 2)), b);
+    }
+
+    #[test]
+    fn flood_fill_follows_the_neighbourhood() {
+        let grid: Vec<Vec<char>> = ["A.", ".A"].iter().map(|row| row.chars().collect()).collect();
+        let orthogonal = flood_fill(&grid, Position::new(0, 0), &Neighbourhood::VonNeumann, |a, b| a == b);
+        assert_eq!(orthogonal, vec![Position::new(0, 0)]);
+        let diagonal = flood_fill(&grid, Position::new(0, 0), &Neighbourhood::Moore, |a, b| a == b);
+        assert_eq!(diagonal, vec![Position::new(0, 0), Position::new(1, 1)]);
+    }
+}
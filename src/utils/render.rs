@@ -0,0 +1,233 @@
+use std::collections::HashSet;
+use std::env;
+use std::io::IsTerminal;
+use crate::utils::grid::{Bounds, Grid};
+use crate::utils::position::Position;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Grey,
+}
+
+impl Colour {
+    const PALETTE: [Colour; 6] = [Colour::Red, Colour::Green, Colour::Yellow, Colour::Blue, Colour::Magenta, Colour::Cyan];
+
+    // Cycles through the bright colours, handy for giving every region its own one
+    pub fn nth(index: usize) -> Colour {
+        Colour::PALETTE[index % Colour::PALETTE.len()]
+    }
+
+    fn ansi_code(&self) -> u8 {
+        match self {
+            Colour::Red => 31,
+            Colour::Green => 32,
+            Colour::Yellow => 33,
+            Colour::Blue => 34,
+            Colour::Magenta => 35,
+            Colour::Cyan => 36,
+            Colour::Grey => 90,
+        }
+    }
+}
+
+pub fn paint(text: &str, colour: Colour) -> String {
+    format!("\x1b[{}m{}\x1b[0m", colour.ansi_code(), text)
+}
+
+// Colours only make sense when the log ends up in a terminal, and NO_COLOR always wins
+pub fn colours_supported() -> bool {
+    std::io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+/*
+    A named set of cells drawn over the base grid.
+    With a symbol the cell shows that character, without one it keeps the grid's character
+    and only picks up the colour.
+ */
+#[derive(Debug, Clone)]
+pub struct Overlay {
+    name: String,
+    symbol: Option<char>,
+    colour: Option<Colour>,
+    cells: HashSet<Position<i64>>,
+}
+
+impl Overlay {
+    pub fn new<I>(name: &str, cells: I) -> Self
+    where
+        I: IntoIterator<Item = Position<i64>>,
+    {
+        Self { name: name.to_string(), symbol: None, colour: None, cells: cells.into_iter().collect() }
+    }
+
+    pub fn symbol(mut self, symbol: char) -> Self {
+        self.symbol = Some(symbol);
+        self
+    }
+
+    pub fn colour(mut self, colour: Colour) -> Self {
+        self.colour = Some(colour);
+        self
+    }
+}
+
+/*
+    Draws any Grid with overlays layered on top, later overlays win.
+
+        Renderer::new(&grid, |c| *c)
+            .overlay(Overlay::new("guard", [position]).symbol('^').colour(Colour::Red))
+            .colours(render::colours_supported())
+            .legend(true)
+            .render()
+ */
+pub struct Renderer<'a, T, G: Grid<T>, F: Fn(&T) -> char> {
+    grid: &'a G,
+    to_char: F,
+    overlays: Vec<Overlay>,
+    colours: bool,
+    legend: bool,
+    viewport: Option<Bounds>,
+    _cell: std::marker::PhantomData<T>,
+}
+
+impl<'a, T, G: Grid<T>, F: Fn(&T) -> char> Renderer<'a, T, G, F> {
+    pub fn new(grid: &'a G, to_char: F) -> Self {
+        Self {
+            grid,
+            to_char,
+            overlays: Vec::new(),
+            colours: false,
+            legend: false,
+            viewport: None,
+            _cell: std::marker::PhantomData,
+        }
+    }
+
+    pub fn overlay(mut self, overlay: Overlay) -> Self {
+        self.overlays.push(overlay);
+        self
+    }
+
+    pub fn colours(mut self, enabled: bool) -> Self {
+        self.colours = enabled;
+        self
+    }
+
+    pub fn legend(mut self, enabled: bool) -> Self {
+        self.legend = enabled;
+        self
+    }
+
+    // Only draws the part of the grid inside the given box, for maps too big to read whole
+    pub fn viewport(mut self, viewport: Bounds) -> Self {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        let bounds = match (self.grid.bounds(), self.viewport) {
+            (None, _) => return output,
+            (Some(bounds), None) => bounds,
+            (Some(bounds), Some(viewport)) => Bounds {
+                min: Position::new(bounds.min.x.max(viewport.min.x), bounds.min.y.max(viewport.min.y)),
+                max: Position::new(bounds.max.x.min(viewport.max.x), bounds.max.y.min(viewport.max.y)),
+            },
+        };
+        for y in bounds.min.y..=bounds.max.y {
+            for x in bounds.min.x..=bounds.max.x {
+                let position = Position::new(x, y);
+                let mut c = self.grid.get(&position).map_or(' ', &self.to_char);
+                let mut colour = None;
+                for overlay in self.overlays.iter().filter(|overlay| overlay.cells.contains(&position)) {
+                    c = overlay.symbol.unwrap_or(c);
+                    colour = overlay.colour.or(colour);
+                }
+                self.push_cell(&mut output, c, colour);
+            }
+            output.push('\n');
+        }
+        if self.legend {
+            for overlay in &self.overlays {
+                output.push_str("  ");
+                self.push_cell(&mut output, overlay.symbol.unwrap_or(' '), overlay.colour);
+                output.push(' ');
+                output.push_str(&overlay.name);
+                output.push('\n');
+            }
+        }
+        output
+    }
+
+    fn push_cell(&self, output: &mut String, c: char, colour: Option<Colour>) {
+        match colour {
+            Some(colour) if self.colours => output.push_str(&paint(&c.to_string(), colour)),
+            _ => output.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Vec<Vec<char>> {
+        vec!["abc".chars().collect(), "def".chars().collect(), "ghi".chars().collect()]
+    }
+
+    #[test]
+    fn later_overlays_win() {
+        let grid = grid();
+        let output = Renderer::new(&grid, |c| *c)
+            .overlay(Overlay::new("first", [Position::new(0, 0), Position::new(1, 0)]).symbol('1'))
+            .overlay(Overlay::new("second", [Position::new(1, 0)]).symbol('2'))
+            .render();
+        assert_eq!(output, "12c\ndef\nghi\n");
+    }
+
+    #[test]
+    fn overlay_without_a_symbol_keeps_the_grid_char() {
+        let grid = grid();
+        let renderer = Renderer::new(&grid, |c| *c)
+            .overlay(Overlay::new("tint", [Position::new(1, 1)]).colour(Colour::Green));
+        assert_eq!(renderer.render(), "abc\ndef\nghi\n");
+        assert_eq!(renderer.colours(true).render(), format!("abc\nd{}f\nghi\n", paint("e", Colour::Green)));
+    }
+
+    #[test]
+    fn viewport_is_clipped_to_the_grid() {
+        let grid = grid();
+        let viewport = Bounds { min: Position::new(1, -5), max: Position::new(10, 1) };
+        let output = Renderer::new(&grid, |c| *c).viewport(viewport).render();
+        assert_eq!(output, "bc\nef\n");
+    }
+
+    #[test]
+    fn legend_lists_every_overlay_in_order() {
+        let grid = vec![vec!['.']];
+        let output = Renderer::new(&grid, |c| *c)
+            .overlay(Overlay::new("guard", [Position::new(0, 0)]).symbol('^').colour(Colour::Red))
+            .overlay(Overlay::new("path", []).colour(Colour::Grey))
+            .legend(true)
+            .render();
+        assert_eq!(output, "^\n  ^ guard\n    path\n");
+    }
+
+    #[test]
+    fn colours_are_only_written_when_enabled() {
+        let grid = vec![vec!['.', '.']];
+        let renderer = Renderer::new(&grid, |c| *c)
+            .overlay(Overlay::new("guard", [Position::new(1, 0)]).symbol('^').colour(Colour::Red))
+            .legend(true);
+        assert_eq!(renderer.render(), ".^\n  ^ guard\n");
+        let coloured = renderer.colours(true).render();
+        assert_eq!(coloured, format!(".{}\n  {} guard\n", paint("^", Colour::Red), paint("^", Colour::Red)));
+        assert_eq!(coloured.replace("\x1b[31m", "").replace("\x1b[0m", ""), ".^\n  ^ guard\n");
+    }
+}
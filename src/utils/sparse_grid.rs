@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use crate::utils::grid::{Bounds, Grid};
+use crate::utils::position::Position;
+
+/*
+    Unbounded grid that only stores the cells that were set.
+    Every other position reads as the default value, and the bounding box grows with the contents.
+ */
+// For puzzles on an unbounded plane, none this year so far
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Position<i64>, T>,
+    default: T,
+}
+
+#[allow(dead_code)]
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        Self { cells: HashMap::new(), default }
+    }
+
+    pub fn insert(&mut self, position: Position<i64>, value: T) -> Option<T> {
+        self.cells.insert(position, value)
+    }
+
+    pub fn remove(&mut self, position: &Position<i64>) -> Option<T> {
+        self.cells.remove(position)
+    }
+
+    pub fn is_set(&self, position: &Position<i64>) -> bool {
+        self.cells.contains_key(position)
+    }
+
+    // Number of stored cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Position<i64>, &T)> {
+        self.cells.iter()
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Clone + PartialEq> SparseGrid<T> {
+    // Keeps only the cells of a dense grid that differ from the default
+    pub fn from_dense(grid: &[Vec<T>], default: T) -> Self {
+        let mut sparse = Self::new(default);
+        for (y, row) in grid.iter().enumerate() {
+            for (x, value) in row.iter().enumerate() {
+                if *value != sparse.default {
+                    sparse.insert(Position::new(x as i64, y as i64), value.clone());
+                }
+            }
+        }
+        sparse
+    }
+}
+
+impl<T> Grid<T> for SparseGrid<T> {
+    fn get(&self, position: &Position<i64>) -> Option<&T> {
+        Some(self.cells.get(position).unwrap_or(&self.default))
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        let mut positions = self.cells.keys();
+        let first = positions.next()?;
+        let mut bounds = Bounds { min: *first, max: *first };
+        for position in positions {
+            bounds.min.x = bounds.min.x.min(position.x);
+            bounds.min.y = bounds.min.y.min(position.y);
+            bounds.max.x = bounds.max.x.max(position.x);
+            bounds.max.y = bounds.max.y.max(position.y);
+        }
+        Some(bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_cells_read_as_the_default() {
+        let mut grid = SparseGrid::new('.');
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounds(), None);
+
+        grid.insert(Position::new(-2, 3), '#');
+        grid.insert(Position::new(4, -1), '#');
+        assert_eq!(grid.len(), 2);
+        assert!(grid.is_set(&Position::new(-2, 3)));
+        assert_eq!(grid.get(&Position::new(100, 100)), Some(&'.'));
+        assert_eq!(grid.bounds(), Some(Bounds { min: Position::new(-2, -1), max: Position::new(4, 3) }));
+
+        assert_eq!(grid.remove(&Position::new(4, -1)), Some('#'));
+        assert_eq!(grid.iter().collect::<Vec<_>>(), vec![(&Position::new(-2, 3), &'#')]);
+    }
+
+    #[test]
+    fn from_dense_keeps_only_differing_cells() {
+        let dense = vec![vec!['.', '#', '.'], vec!['#', '.', '.']];
+        let sparse = SparseGrid::from_dense(&dense, '.');
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(sparse.render(|c| *c), ".#\n#.\n");
+    }
+}
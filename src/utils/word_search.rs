@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+use crate::utils::direction::Direction;
+use crate::utils::position::Position;
+
+// One occurrence of a word, read from start one step in direction at a time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    pub(crate) word: String,
+    pub(crate) start: Position<i64>,
+    pub(crate) direction: Direction,
+}
+
+/*
+    Finds words in a rectangular letter grid along all eight directions.
+    With wrap-around a word running off one edge carries on from the opposite one.
+
+    Every hit is one run of cells, so a palindrome read forwards from one end and backwards
+    from the other is a single hit, as are the different ways a wrapped word can reach the
+    same cells. Only the first way found, scanning rows top to bottom, is reported.
+ */
+pub struct WordSearch<'a> {
+    grid: &'a [Vec<char>],
+    wrap_around: bool,
+}
+
+impl<'a> WordSearch<'a> {
+    pub fn new(grid: &'a [Vec<char>]) -> Self {
+        Self { grid, wrap_around: false }
+    }
+
+    // Day 4 searches without wrapping, this is for the puzzle variants that do
+    #[allow(dead_code)]
+    pub fn wrap_around(mut self, enabled: bool) -> Self {
+        self.wrap_around = enabled;
+        self
+    }
+
+    pub fn find(&self, words: &[&str]) -> Vec<Hit> {
+        let mut hits: Vec<Hit> = Vec::new();
+        let mut seen: HashSet<(Vec<char>, Vec<Position<i64>>)> = HashSet::new();
+        for word in words {
+            let letters: Vec<char> = word.chars().collect();
+            if letters.is_empty() {
+                continue;
+            }
+            for (y, row) in self.grid.iter().enumerate() {
+                for (x, c) in row.iter().enumerate() {
+                    if *c != letters[0] {
+                        continue;
+                    }
+                    let start = Position::new(x as i64, y as i64);
+                    for direction in &Direction::ALL_DIRECTIONS {
+                        let cells = match self.trace(&letters, start, direction) {
+                            None => continue,
+                            Some(cells) => cells,
+                        };
+                        if seen.insert((letters.clone(), canonical(cells))) {
+                            hits.push(Hit { word: word.to_string(), start, direction: *direction });
+                        }
+                    }
+                }
+            }
+        }
+        hits
+    }
+
+    // The cells the word covers from start, if the letters all match
+    fn trace(&self, letters: &[char], start: Position<i64>, direction: &Direction) -> Option<Vec<Position<i64>>> {
+        let mut cells: Vec<Position<i64>> = Vec::with_capacity(letters.len());
+        let mut current = start;
+        for letter in letters {
+            current = self.resolve(current)?;
+            if self.grid[current.y as usize][current.x as usize] != *letter {
+                return None;
+            }
+            cells.push(current);
+            current = direction.step(&current);
+        }
+        Some(cells)
+    }
+
+    fn resolve(&self, position: Position<i64>) -> Option<Position<i64>> {
+        let height = self.grid.len() as i64;
+        let width = self.grid.first().map_or(0, |row| row.len() as i64);
+        if self.wrap_around && width > 0 && height > 0 {
+            return Some(Position::new(position.x.rem_euclid(width), position.y.rem_euclid(height)));
+        }
+        if position.x < 0 || position.x >= width || position.y < 0 || position.y >= height {
+            return None;
+        }
+        Some(position)
+    }
+}
+
+// The same cells read backwards spell the same palindrome, so both orders share one key
+fn canonical(cells: Vec<Position<i64>>) -> Vec<Position<i64>> {
+    let reversed: Vec<Position<i64>> = cells.iter().rev().copied().collect();
+    let key = |cells: &[Position<i64>]| cells.iter().map(|cell| (cell.y, cell.x)).collect::<Vec<_>>();
+    if key(&reversed) < key(&cells) {
+        reversed
+    } else {
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn finds_words_in_every_direction() {
+        let letters = grid(&["XMAS", "M..A", "A..M", "SAMX"]);
+        let hits = WordSearch::new(&letters).find(&["XMAS"]);
+        assert_eq!(hits.len(), 4);
+        assert_eq!(hits[0], Hit { word: "XMAS".to_string(), start: Position::new(0, 0), direction: Direction::SOUTH });
+    }
+
+    #[test]
+    fn wrap_around_continues_from_the_opposite_edge() {
+        let letters = grid(&["ASXM"]);
+        assert!(WordSearch::new(&letters).find(&["XMAS"]).is_empty());
+        let hits = WordSearch::new(&letters).wrap_around(true).find(&["XMAS"]);
+        assert_eq!(hits, vec![Hit { word: "XMAS".to_string(), start: Position::new(2, 0), direction: Direction::EAST }]);
+    }
+
+    #[test]
+    fn palindromes_count_once() {
+        let letters = grid(&["ABA"]);
+        assert_eq!(WordSearch::new(&letters).find(&["ABA"]).len(), 1);
+    }
+}